@@ -1,2 +1,10 @@
+use std::io;
+
 pub(crate) mod binary_reader;
+pub(crate) mod binary_writer;
+pub(crate) mod hash;
+pub(crate) mod string_pool;
 
+pub(crate) fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
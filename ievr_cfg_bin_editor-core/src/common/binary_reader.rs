@@ -1,80 +1,146 @@
+use crate::error::CfgBinError;
+
+/// Reads little-endian values from a byte slice.
+///
+/// Every read is bounds-checked: reading or seeking past the end of the file returns a
+/// [`CfgBinError::Truncated`] with the failing offset and the name of the section being read.
+#[derive(Clone)]
 pub struct BinaryReader<'a> {
     file: &'a [u8],
     position: usize,
+    /// Where `file` starts in the whole file, so that windows report absolute offsets
+    base: usize,
+    section: &'static str,
 }
 
 impl<'a> BinaryReader<'a> {
     pub fn new(file: &'a [u8]) -> BinaryReader<'a> {
-        BinaryReader { file, position: 0 }
+        BinaryReader { file, position: 0, base: 0, section: "file" }
+    }
+
+    /// Sets the section name reported by the errors of the following reads
+    pub fn set_section(&mut self, section: &'static str) {
+        self.section = section;
+    }
+
+    pub fn section(&self) -> &'static str {
+        self.section
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, CfgBinError> {
+        Ok(u32::from_le_bytes(self.read_array()?))
     }
 
-    pub fn read_u32(&mut self) -> u32 {
-        let v = u32::from_le_bytes(self.file[self.position..self.position + 4].try_into().unwrap());
-        self.position += 4;
-        v
+    pub fn read_i64(&mut self) -> Result<i64, CfgBinError> {
+        Ok(i64::from_le_bytes(self.read_array()?))
     }
 
-    pub fn read_i64(&mut self) -> i64 {
-        let v = i64::from_le_bytes(self.file[self.position..self.position + 8].try_into().unwrap());
-        self.position += 8;
-        v
+    pub fn read_i32(&mut self) -> Result<i32, CfgBinError> {
+        Ok(i32::from_le_bytes(self.read_array()?))
     }
 
-    pub fn read_i32(&mut self) -> i32 {
-        let v = i32::from_le_bytes(self.file[self.position..self.position + 4].try_into().unwrap());
-        self.position += 4;
-        v
+    pub fn read_i16(&mut self) -> Result<i16, CfgBinError> {
+        Ok(i16::from_le_bytes(self.read_array()?))
     }
 
-    pub fn read_i16(&mut self) -> i16 {
-        let v = i16::from_le_bytes(self.file[self.position..self.position + 2].try_into().unwrap());
-        self.position += 2;
-        v
+    pub fn read_u16(&mut self) -> Result<u16, CfgBinError> {
+        Ok(u16::from_le_bytes(self.read_array()?))
     }
 
-    pub fn read_f32(&mut self) -> f32 {
-        let v = f32::from_le_bytes(self.file[self.position..self.position + 4].try_into().unwrap());
-        self.position += 4;
-        v
+    pub fn read_f32(&mut self) -> Result<f32, CfgBinError> {
+        Ok(f32::from_le_bytes(self.read_array()?))
     }
 
-    pub fn read_bool(&mut self) -> bool {
-        let v = self.read_i32();
-        v != 0
+    pub fn read_f64(&mut self) -> Result<f64, CfgBinError> {
+        Ok(f64::from_le_bytes(self.read_array()?))
     }
 
-    pub fn read_bytes(&mut self, count: usize) -> &[u8] {
-        let v = &self.file[self.position..self.position + count];
+    pub fn read_bool(&mut self) -> Result<bool, CfgBinError> {
+        let v = self.read_i32()?;
+        Ok(v != 0)
+    }
+
+    pub fn read_bytes(&mut self, count: usize) -> Result<&'a [u8], CfgBinError> {
+        let v = self.peek_bytes(count)?;
         self.position += count;
-        v
+        Ok(v)
+    }
+
+    pub fn read_byte(&mut self) -> Result<u8, CfgBinError> {
+        Ok(self.read_array::<1>()?[0])
     }
 
-    pub fn read_byte(&mut self) -> u8 {
-        let v = self.file[self.position];
-        self.position += 1;
-        v
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], CfgBinError> {
+        let v = self.peek_bytes(N)?.try_into().unwrap();
+        self.position += N;
+        Ok(v)
     }
 
-    pub fn skip(&mut self, delta: usize) {
-        self.position += delta;
+    /// Reads a `u32` without moving the position
+    pub fn peek_u32(&self) -> Result<u32, CfgBinError> {
+        Ok(u32::from_le_bytes(self.peek_bytes(4)?.try_into().unwrap()))
+    }
+
+    /// Returns the next `count` bytes without moving the position
+    pub fn peek_bytes(&self, count: usize) -> Result<&'a [u8], CfgBinError> {
+        self.ensure_available(self.position, count, self.section)?;
+        Ok(&self.file[self.position..self.position + count])
+    }
+
+    /// Returns a reader over the `size` bytes starting at `position`.
+    /// Its errors report offsets in the whole file and are attributed to `section`.
+    pub fn window(&self, position: usize, size: usize, section: &'static str) -> Result<BinaryReader<'a>, CfgBinError> {
+        self.ensure_available(position, size, section)?;
+
+        Ok(BinaryReader {
+            file: &self.file[position..position + size],
+            position: 0,
+            base: self.base + position,
+            section,
+        })
+    }
+
+    pub fn skip(&mut self, delta: usize) -> Result<(), CfgBinError> {
+        self.set_position(self.position.saturating_add(delta))
     }
 
     pub fn position(&self) -> usize {
         self.position
     }
 
-    pub fn seek_alignment(&mut self, align: usize) {
-        while self.position % align != 0 {
-            self.position += 1;
-        }
+    /// The current position in the whole file, which differs from [`BinaryReader::position`] in a window
+    pub fn file_position(&self) -> usize {
+        self.base + self.position
     }
 
-    pub fn set_position(&mut self, position: usize) {
+    pub fn seek_alignment(&mut self, align: usize) -> Result<(), CfgBinError> {
+        self.set_position(self.position.next_multiple_of(align))
+    }
+
+    /// Moves to `position`, which can be at most the end of the file
+    pub fn set_position(&mut self, position: usize) -> Result<(), CfgBinError> {
+        if position > self.file.len() {
+            return Err(CfgBinError::Truncated { offset: self.base + self.position, section: self.section, size: position - self.position });
+        }
+
         self.position = position;
+        Ok(())
     }
 
     pub fn file_size(&self) -> usize {
         self.file.len()
     }
-}
 
+    /// The bytes from the current position to the end of the file
+    pub fn remaining_bytes(&self) -> &'a [u8] {
+        &self.file[self.position..]
+    }
+
+    /// Checks that `size` bytes can be read starting at `position`
+    pub fn ensure_available(&self, position: usize, size: usize, section: &'static str) -> Result<(), CfgBinError> {
+        match position.checked_add(size) {
+            Some(end) if end <= self.file.len() => Ok(()),
+            _ => Err(CfgBinError::Truncated { offset: self.base + position, section, size }),
+        }
+    }
+}
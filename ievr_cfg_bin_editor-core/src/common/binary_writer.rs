@@ -0,0 +1,85 @@
+pub struct BinaryWriter {
+    file: Vec<u8>,
+    position: usize,
+}
+
+impl BinaryWriter {
+    pub fn new() -> BinaryWriter {
+        BinaryWriter { file: Vec::new(), position: 0 }
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn write_i64(&mut self, value: i64) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn write_i32(&mut self, value: i32) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn write_i16(&mut self, value: i16) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn write_u16(&mut self, value: u16) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn write_f32(&mut self, value: f32) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn write_f64(&mut self, value: f64) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn write_bool(&mut self, value: bool) {
+        self.write_i32(value as i32);
+    }
+
+    pub fn write_byte(&mut self, value: u8) {
+        self.write_bytes(&[value]);
+    }
+
+    /// Writes the bytes at the current position, overwriting what is already there and growing the file if needed
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        let end = self.position + bytes.len();
+        if end > self.file.len() {
+            self.file.resize(end, 0);
+        }
+
+        self.file[self.position..end].copy_from_slice(bytes);
+        self.position = end;
+    }
+
+    /// Moves forward by `delta` bytes, zero-filling any bytes past the current end of the file
+    pub fn skip(&mut self, delta: usize) {
+        self.set_position(self.position + delta);
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn seek_alignment(&mut self, align: usize) {
+        self.set_position(self.position.next_multiple_of(align));
+    }
+
+    pub fn set_position(&mut self, position: usize) {
+        if position > self.file.len() {
+            self.file.resize(position, 0);
+        }
+        self.position = position;
+    }
+
+    pub fn file_size(&self) -> usize {
+        self.file.len()
+    }
+
+    pub fn into_inner(self) -> Vec<u8> {
+        self.file
+    }
+}
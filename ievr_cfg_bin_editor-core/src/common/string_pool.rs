@@ -0,0 +1,36 @@
+use std::collections::HashMap;
+
+/// A null-terminated string table where identical strings are only stored once
+#[derive(Default)]
+pub struct StringPool {
+    data: Vec<u8>,
+    offsets: HashMap<Vec<u8>, u32>,
+}
+
+impl StringPool {
+    pub fn new() -> StringPool {
+        StringPool::default()
+    }
+
+    /// Adds the string to the pool if it isn't already in there and returns its offset
+    pub fn add(&mut self, string: &[u8]) -> u32 {
+        if let Some(offset) = self.offsets.get(string) {
+            return *offset
+        }
+
+        let offset = self.data.len() as u32;
+        self.data.extend_from_slice(string);
+        self.data.push(0);
+        self.offsets.insert(string.to_vec(), offset);
+
+        offset
+    }
+
+    pub fn count(&self) -> usize {
+        self.offsets.len()
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
@@ -1,24 +1,48 @@
 #![allow(dead_code)]
 
-use std::collections::HashMap;
+use std::{collections::{BTreeMap, HashMap}, io::{self, Write}};
 
-use crate::{rdbn::Rdbn, t2b::{T2b, T2bEntry}};
+use crate::{
+    common::invalid_data,
+    dictionary::HashDictionary,
+    rdbn::{Rdbn, MAX_ENTRY_COUNT, RdbnFieldDeclaration, RdbnListEntry, RdbnOpaqueHeader, RdbnTypeDeclaration},
+    t2b::{T2b, T2bEncoding, T2bEntry, T2bValue, ValueLength},
+};
 
 mod utils;
+mod metadata;
+mod validation;
+mod composite;
+mod cell;
+mod rows;
+mod json_float;
 
 use serde::{Deserialize, Serialize};
 pub use utils::*;
+pub use metadata::*;
+pub use validation::*;
+pub use composite::*;
+pub use cell::*;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Database {
     source: DatabaseSource,
     tables: Vec<Table>,
+    #[serde(default)]
+    metadata: FormatMetadata,
+    /// The names of the hash values in the tables, found in the string table of the file or in a dictionary
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    hash_names: BTreeMap<u32, String>,
 }
 
 impl Database {
     pub fn serialize(&self) -> String {
-        let json = serde_json::to_string_pretty(&self).unwrap();
-        json
+        serde_json::to_string_pretty(&self).unwrap()
+    }
+
+    /// Reads back a database produced by [`Database::serialize`]
+    pub fn from_json(json: &str) -> serde_json::Result<Database> {
+        serde_json::from_str(json)
     }
 
     pub fn tables(&self) -> &Vec<Table> {
@@ -32,11 +56,101 @@ impl Database {
     pub fn table_mut(&mut self, name: &str) -> Option<&mut Table> {
         self.tables.iter_mut().find(|table| table.name == name)
     }
+
+    /// Details of the file the database was read from, such as which variant of the format it uses
+    pub fn metadata(&self) -> &FormatMetadata {
+        &self.metadata
+    }
+
+    pub fn metadata_mut(&mut self) -> &mut FormatMetadata {
+        &mut self.metadata
+    }
+
+    /// The name of a hash value, if it is known
+    pub fn hash_name(&self, hash: u32) -> Option<&str> {
+        self.hash_names.get(&hash).map(String::as_str)
+    }
+
+    pub fn hash_names(&self) -> &BTreeMap<u32, String> {
+        &self.hash_names
+    }
+
+    /// Names the hash values of the tables that are still unknown with the ones of `dictionary`.
+    /// Returns how many hashes got a name.
+    pub fn resolve_hashes(&mut self, dictionary: &HashDictionary) -> usize {
+        let mut count = 0;
+
+        for hash in hash_values(&self.tables) {
+            if !self.hash_names.contains_key(&hash) && let Some(name) = dictionary.get(hash) {
+                self.hash_names.insert(hash, name.to_string());
+                count += 1;
+            }
+        }
+
+        count
+    }
+
+    /// The string encoding of a T2B file, `None` for RDBN files
+    pub fn encoding(&self) -> Option<T2bEncoding> {
+        match &self.metadata {
+            FormatMetadata::T2b(metadata) => Some(metadata.encoding),
+            _ => None,
+        }
+    }
+
+    /// Encodes the database back into the binary format it was read from
+    pub fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        match self.source {
+            DatabaseSource::RDBN => Rdbn::try_from(self)?.to_bytes(),
+            DatabaseSource::T2B => T2b::try_from(self)?.to_bytes(),
+        }
+    }
+
+    pub fn write_to<W: Write>(&self, output: &mut W) -> io::Result<()> {
+        output.write_all(&self.to_bytes()?)
+    }
+
+    /// Encodes the database like [`Database::to_bytes`], but takes the layout details that the database has no metadata for
+    /// (RDBN header, string table and field offsets and sizes, T2B encoding, value length and hash type) from an original file
+    pub fn to_bytes_with_template(&self, template: &[u8]) -> io::Result<Vec<u8>> {
+        match self.source {
+            DatabaseSource::RDBN => {
+                let template = Rdbn::read(template)?;
+
+                let mut rdbn = Rdbn::try_from(self)?;
+                let unused_types = match &self.metadata {
+                    FormatMetadata::Rdbn(metadata) => metadata.unused_types.clone(),
+                    _ => unused_types(&template),
+                };
+                apply_rdbn_template(&mut rdbn, &template, unused_types)?;
+                if let FormatMetadata::None = self.metadata {
+                    rdbn.header = template.header;
+                    rdbn.string_lookup = template.string_lookup;
+                    rdbn.dictionary_names = template.dictionary_names;
+                }
+                rdbn.to_bytes()
+            },
+            DatabaseSource::T2B => {
+                let template = T2b::read(template)?;
+
+                let mut t2b = T2b::try_from(self)?;
+                if let FormatMetadata::None = self.metadata {
+                    t2b.encoding = template.encoding;
+                    t2b.value_length = template.value_length;
+                    t2b.hash_type = template.hash_type;
+                    t2b.opaque = template.opaque;
+                    t2b.dictionary_names = template.dictionary_names;
+                }
+                t2b.to_bytes()
+            },
+        }
+    }
 }
 
 impl From<Rdbn> for Database {
     fn from(rdbn: Rdbn) -> Self {
-        let tables = rdbn.lists.iter().map(|list| { // For each list
+        let unused_types = unused_types(&rdbn);
+        let tables: Vec<Table> = rdbn.lists.iter().map(|list| { // For each list
             let schema = &rdbn.types[list.type_index];
 
             Table {
@@ -47,75 +161,390 @@ impl From<Rdbn> for Database {
                         name: f.name.clone(),
                         value_type: ValueType::Rdbn(f.field_type),
                         count: f.count as usize,
+                        optional: false,
+                        metadata: Some(FieldMetadata { size: f.size, offset: f.offset, category: f.field_type_category }),
                     }).collect(),
+                    metadata: Some(SchemaMetadata {
+                        unk_hash: schema.unk_hash,
+                        size: list.value_size,
+                        type_index: Some(list.type_index),
+                        field_index: schema.field_index,
+                    }),
                 },
                 rows: list.values.iter().map(|row| {
                     Row {
                         values: row.iter().map(|values| {
                             values.iter().map(Value::from).collect() // We convert every value in the database to the abstracted one
                         }).collect(),
+                        position: None,
+                        signature: None,
                     }
                 }).collect(),
+                unk1: list.unk1,
             }
         }).collect();
 
-        Database { source: DatabaseSource::RDBN, tables }
+        let hash_names = hash_values(&tables)
+            .filter_map(|hash| Some((hash, rdbn.string(hash)?.to_string())))
+            .collect();
+
+        let metadata = FormatMetadata::Rdbn(RdbnMetadata {
+            header: rdbn.header,
+            strings: rdbn.string_lookup,
+            dictionary_names: rdbn.dictionary_names,
+            unused_types,
+        });
+        Database { source: DatabaseSource::RDBN, tables, metadata, hash_names }
     }
 }
 
 impl From<T2b> for Database {
     fn from(t2b: T2b) -> Self {
-        let mut t2b_iter = t2b.entries.into_iter();
-
-        t2b_iter.next().unwrap(); // The first info is the size of the file. It is not useful since we want to group by name
-        
-        let mut tables: Vec<Vec<T2bEntry>> = Vec::new();
+        let metadata = FormatMetadata::T2b(T2bMetadata {
+            encoding: t2b.encoding,
+            value_length: t2b.value_length,
+            hash_type: t2b.hash_type,
+            opaque: t2b.opaque,
+            dictionary_names: t2b.dictionary_names,
+        });
+        // The entries are grouped by name, and every row remembers where its entry was so that the order can be restored.
+        // The first entry, which holds the size of the file, is kept as a table of its own.
+        let mut tables: Vec<Vec<(usize, T2bEntry)>> = Vec::new();
 
         let mut hash_map: HashMap<String, usize> = HashMap::new();
         let mut index = 0;
 
-        for entry in t2b_iter {
+        for (position, entry) in t2b.entries.into_iter().enumerate() {
             let i = hash_map.entry(entry.name.clone()).or_insert_with(|| {
                 let idx = index;
                 tables.push(Vec::new());
                 index += 1;
                 idx
             });
-            tables[*i].push(entry);
+            tables[*i].push((position, entry));
         };
 
         let tables = tables.into_iter().map( |table| {
-            let name = table[0].name.clone();
+            let name = table[0].1.name.clone();
 
-            let schema = Schema {
-                name: String::new(),
-                fields: table[0].values.iter().map(|value| Field {
+            // Entries of the same name can have different values. The schema has a column for the longest entry,
+            // typed after the first entry that has it, and the columns that some entries lack are optional.
+            let column_count = table.iter().map(|(_, entry)| entry.values.len()).max().unwrap_or(0);
+            let required_columns = table.iter().map(|(_, entry)| entry.values.len()).min().unwrap_or(0);
+
+            let fields: Vec<Field> = (0..column_count).map(|i| {
+                let value_type = table.iter().find_map(|(_, entry)| entry.values.get(i)).map(|value| value.r#type).unwrap();
+
+                Field {
                     name: String::new(),
-                    value_type: ValueType::T2b(value.r#type),
+                    value_type: ValueType::T2b(value_type),
                     count: 1,
-                }).collect()
-            };
+                    optional: i >= required_columns,
+                    metadata: None,
+                }
+            }).collect();
 
             let mut rows = Vec::with_capacity(table.len());
-            for entry in table {
+            for (position, entry) in table {
                 let values = entry.values.iter().map(|value | {
                     vec![Value::from(value)]
                 }).collect();
 
-                rows.push(Row { values });
+                // Entries whose types differ from the columns keep their own
+                let signature = entry.values.iter().zip(&fields).any(|(value, field)| field.value_type != ValueType::T2b(value.r#type))
+                    .then(|| entry.values.iter().map(|value| ValueType::T2b(value.r#type)).collect());
+
+                rows.push(Row { values, position: Some(position), signature });
             }
 
+            let schema = Schema {
+                name: String::new(),
+                fields,
+                metadata: None,
+            };
+
             Table {
                 name,
                 schema,
                 rows,
+                unk1: 0,
             }
         }).collect();
 
-        Database { source: DatabaseSource::T2B, tables }
+        Database { source: DatabaseSource::T2B, tables, metadata, hash_names: BTreeMap::new() }
+    }
+}
+
+impl TryFrom<&Database> for Rdbn {
+    type Error = io::Error;
+
+    fn try_from(database: &Database) -> io::Result<Rdbn> {
+        // The types are placed where they were in the source file, and the places nobody claims are removed at the end
+        let mut types: Vec<Option<RdbnTypeDeclaration>> = Vec::new();
+        let mut lists = Vec::with_capacity(database.tables.len());
+
+        if let FormatMetadata::Rdbn(metadata) = &database.metadata {
+            place_unused_types(&mut types, metadata.unused_types.clone())?;
+        }
+
+        for table in &database.tables {
+            let fields = table.schema.fields.iter().enumerate().map(|(i, field)| {
+                let ValueType::Rdbn(field_type) = field.value_type else {
+                    return Err(invalid_data(format!("Field {} of table {} is not an RDBN field", field.name, table.name)))
+                };
+
+                if let Some(metadata) = &field.metadata {
+                    return Ok(RdbnFieldDeclaration {
+                        name: field.name.clone(),
+                        count: field.count as i32,
+                        size: metadata.size,
+                        offset: metadata.offset,
+                        field_type,
+                        field_type_category: metadata.category,
+                    })
+                }
+
+                // Composite values and values of unknown types don't have a fixed size, so it is taken from the first row
+                let size = field_type.size().unwrap_or_else(|| {
+                    match table.rows.first().and_then(|row| row.values.get(i)).and_then(|values| values.first()) {
+                        Some(Value::Bytes(bytes)) => bytes.len() as i32,
+                        Some(Value::Composite(composite)) => composite.raw().len() as i32,
+                        _ => 0,
+                    }
+                });
+
+                Ok(RdbnFieldDeclaration {
+                    name: field.name.clone(),
+                    count: field.count as i32,
+                    size,
+                    offset: 0,
+                    field_type,
+                    field_type_category: field_type.into(),
+                })
+            }).collect::<io::Result<Vec<_>>>()?;
+
+            let mut type_declaration = RdbnTypeDeclaration {
+                name: table.schema.name.clone(),
+                unk_hash: table.schema.metadata.as_ref().map_or(0, |metadata| metadata.unk_hash),
+                fields,
+                field_index: table.schema.metadata.as_ref().and_then(|metadata| metadata.field_index),
+            };
+
+            // The layout of the source file is kept as long as it still holds every field
+            let value_size = match &table.schema.metadata {
+                Some(metadata) if table.schema.fields.iter().all(|field| field.metadata.is_some()) && type_declaration.fits_in(metadata.size) => metadata.size,
+                _ => type_declaration.compute_layout(),
+            };
+
+            let wanted_index = table.schema.metadata.as_ref().and_then(|metadata| metadata.type_index);
+            if let Some(index) = wanted_index && index >= MAX_ENTRY_COUNT {
+                return Err(invalid_data(format!("Table {} has the type index {index}, but a file holds at most {MAX_ENTRY_COUNT} types", table.name)))
+            }
+            let type_index = match wanted_index {
+                Some(index) if types.get(index).is_none_or(|ty| ty.as_ref().is_none_or(|ty| *ty == type_declaration)) => {
+                    if types.len() <= index {
+                        types.resize(index + 1, None);
+                    }
+                    types[index] = Some(type_declaration);
+                    index
+                },
+                _ => match types.iter().position(|ty| ty.as_ref() == Some(&type_declaration)) {
+                    Some(index) => index,
+                    None => {
+                        types.push(Some(type_declaration));
+                        types.len() - 1
+                    }
+                },
+            };
+            let fields = &types[type_index].as_ref().unwrap().fields;
+
+            let values = table.rows.iter().enumerate().map(|(j, row)| {
+                if row.values.len() != fields.len() {
+                    return Err(invalid_data(format!("Row {j} of table {} has {} fields instead of {}", table.name, row.values.len(), fields.len())))
+                }
+
+                row.values.iter().zip(fields).map(|(values, field)| {
+                    values.iter().map(|value| value.to_rdbn(field.field_type).ok_or_else(|| invalid_data(
+                        format!("Row {j} of table {}: field {} of type {:?} cannot hold {:?}", table.name, field.name, field.field_type, value)
+                    ))).collect()
+                }).collect()
+            }).collect::<io::Result<Vec<_>>>()?;
+
+            lists.push(RdbnListEntry {
+                name: table.name.clone(),
+                type_index,
+                value_size,
+                unk1: table.unk1,
+                values,
+            });
+        }
+
+        let types = compact_types(types, &mut lists);
+
+        let (header, string_lookup, dictionary_names) = match &database.metadata {
+            FormatMetadata::Rdbn(metadata) => (metadata.header.clone(), metadata.strings.clone(), metadata.dictionary_names.clone()),
+            _ => (RdbnOpaqueHeader::default(), Vec::new(), BTreeMap::new()),
+        };
+
+        Ok(Rdbn { header, types, lists, string_lookup, dictionary_names })
+    }
+}
+
+impl TryFrom<&Database> for T2b {
+    type Error = io::Error;
+
+    fn try_from(database: &Database) -> io::Result<T2b> {
+        let mut entries = Vec::new();
+
+        for table in &database.tables {
+            if let Some(mismatch) = table.validate().into_iter().next() {
+                return Err(mismatch.into())
+            }
+
+            // Rows are sorted by the position of their entry, and the ones without follow the previous row of the table.
+            // The rows before the first one with a position precede it.
+            let leading = table.rows.iter().take_while(|row| row.position.is_none()).count();
+            let mut anchor = table.rows.get(leading).and_then(|row| row.position).unwrap_or(usize::MAX);
+            let mut following = -(leading as isize) - 1;
+
+            for (j, row) in table.rows.iter().enumerate() {
+                match row.position {
+                    Some(position) => {
+                        anchor = position;
+                        following = 0;
+                    },
+                    None => following += 1,
+                }
+
+                let values = row.values.iter().flatten().map(|value| {
+                    value.to_t2b().ok_or_else(|| invalid_data(
+                        format!("Row {j} of table {} holds {:?}, which cannot be stored in a T2B file", table.name, value)
+                    ))
+                }).collect::<io::Result<Vec<_>>>()?;
+
+                entries.push(((anchor, following), T2bEntry { name: table.name.clone(), values }));
+            }
+        }
+
+        entries.sort_by_key(|(key, _)| *key);
+        let entries: Vec<T2bEntry> = entries.into_iter().map(|(_, entry)| entry).collect();
+
+        let metadata = match &database.metadata {
+            FormatMetadata::T2b(metadata) => metadata.clone(),
+            _ => T2bMetadata::default(),
+        };
+
+        // 64-bit values need the long layout, even if the source file used the short one
+        let value_length = if entries.iter().flat_map(|entry| &entry.values).any(|value| matches!(value.value, T2bValue::Long(_) | T2bValue::F64(_))) {
+            ValueLength::Long
+        } else {
+            metadata.value_length
+        };
+
+        Ok(T2b {
+            entries,
+            encoding: metadata.encoding,
+            value_length,
+            hash_type: metadata.hash_type,
+            opaque: metadata.opaque,
+            dictionary_names: metadata.dictionary_names,
+        })
+    }
+}
+
+/// Every hash value stored in the tables
+fn hash_values(tables: &[Table]) -> impl Iterator<Item = u32> + '_ {
+    tables.iter()
+        .flat_map(|table| &table.rows)
+        .flat_map(|row| row.values.iter().flatten())
+        .filter_map(|value| match value {
+            Value::Hash(hash) => Some(*hash),
+            _ => None,
+        })
+}
+
+/// The types of the file that no list uses, by their index in the type table
+fn unused_types(rdbn: &Rdbn) -> BTreeMap<usize, RdbnTypeDeclaration> {
+    rdbn.types.iter().enumerate()
+        .filter(|(index, _)| rdbn.lists.iter().all(|list| list.type_index != *index))
+        .map(|(index, type_declaration)| (index, type_declaration.clone()))
+        .collect()
+}
+
+/// Puts the types that no list uses back at their index, before the lists claim theirs
+fn place_unused_types(types: &mut Vec<Option<RdbnTypeDeclaration>>, unused_types: BTreeMap<usize, RdbnTypeDeclaration>) -> io::Result<()> {
+    for (index, type_declaration) in unused_types {
+        if index >= MAX_ENTRY_COUNT {
+            return Err(invalid_data(format!("Type {} has the type index {index}, but a file holds at most {MAX_ENTRY_COUNT} types", type_declaration.name)))
+        }
+        if types.len() <= index {
+            types.resize(index + 1, None);
+        }
+        types[index] = Some(type_declaration);
     }
+
+    Ok(())
+}
+
+/// Replaces the computed type layouts by the ones of the template, for every list whose type still has the same fields.
+/// The types that no list uses are placed like in [`place_unused_types`].
+fn apply_rdbn_template(rdbn: &mut Rdbn, template: &Rdbn, unused_types: BTreeMap<usize, RdbnTypeDeclaration>) -> io::Result<()> {
+    let mut types: Vec<Option<RdbnTypeDeclaration>> = Vec::new();
+    place_unused_types(&mut types, unused_types)?;
+
+    for list in &mut rdbn.lists {
+        let mut type_declaration = &rdbn.types[list.type_index];
+        let mut wanted_index = None;
+
+        if let Some(template_list) = template.lists.iter().find(|template_list| template_list.name == list.name) {
+            let template_type = &template.types[template_list.type_index];
+
+            if template_type.has_same_fields(type_declaration) {
+                type_declaration = template_type;
+                list.value_size = template_list.value_size;
+                wanted_index = Some(template_list.type_index);
+            }
+        }
+
+        // The lists keep the type they have in the template, even when another type has the same declaration
+        list.type_index = match wanted_index {
+            Some(index) if types.get(index).is_none_or(|ty| ty.as_ref().is_none_or(|ty| ty == type_declaration)) => {
+                if types.len() <= index {
+                    types.resize(index + 1, None);
+                }
+                types[index] = Some(type_declaration.clone());
+                index
+            },
+            _ => match types.iter().position(|ty| ty.as_ref() == Some(type_declaration)) {
+                Some(index) => index,
+                None => {
+                    types.push(Some(type_declaration.clone()));
+                    types.len() - 1
+                }
+            },
+        };
+    }
+
+    rdbn.types = compact_types(types, &mut rdbn.lists);
+    Ok(())
+}
+
+/// Removes the places of the type table that no list claims, and updates the type indexes of the lists
+fn compact_types(types: Vec<Option<RdbnTypeDeclaration>>, lists: &mut [RdbnListEntry]) -> Vec<RdbnTypeDeclaration> {
+    let mut new_indices = Vec::with_capacity(types.len());
+    let mut type_count = 0;
+    for ty in &types {
+        new_indices.push(type_count);
+        type_count += ty.is_some() as usize;
+    }
+    for list in lists {
+        list.type_index = new_indices[list.type_index];
+    }
+
+    types.into_iter().flatten().collect()
 }
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Serialize, Deserialize)]
 pub enum DatabaseSource {
     RDBN,
@@ -0,0 +1,203 @@
+use std::{error::Error, fmt, io};
+
+use super::{CompositeValue, Field, Row, Schema, Value, ValueType};
+
+/// A value that can't be read from or written to a column of a row
+#[derive(Debug, Clone, PartialEq)]
+pub enum CellError {
+    /// The schema has no field with this name
+    UnknownField { field: String },
+    /// The row, or its signature, ends before the column of the field
+    MissingColumn { field: String },
+    /// The field holds `expected` values, but `found` were given
+    ValueCount { field: String, expected: usize, found: usize },
+    /// The value doesn't fit the type of the field
+    ValueType { field: String, expected: ValueType, found: Value },
+    /// The composite or unknown field holds values of `expected` bytes, but one of `found` bytes was given
+    ValueSize { field: String, expected: usize, found: usize },
+}
+
+impl fmt::Display for CellError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CellError::UnknownField { field } =>
+                write!(f, "There is no field named {field}"),
+            CellError::MissingColumn { field } =>
+                write!(f, "The row ends before the field {field}"),
+            CellError::ValueCount { field, expected, found } =>
+                write!(f, "The field {field} holds {expected} values, not {found}"),
+            CellError::ValueType { field, expected, found } =>
+                write!(f, "The field {field} of type {expected:?} cannot hold {found:?}"),
+            CellError::ValueSize { field, expected, found } =>
+                write!(f, "The field {field} holds values of {expected} bytes, not {found}"),
+        }
+    }
+}
+
+impl Error for CellError {}
+
+impl From<CellError> for io::Error {
+    fn from(error: CellError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, error)
+    }
+}
+
+impl Schema {
+    /// The column of the field with this name
+    pub fn field_index(&self, name: &str) -> Option<usize> {
+        self.fields.iter().position(|field| field.name == name)
+    }
+
+    pub fn field(&self, name: &str) -> Option<&Field> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+}
+
+impl Row {
+    /// The first value of the field, or `None` if the schema has no such field or the row ends before it
+    pub fn get(&self, schema: &Schema, name: &str) -> Option<&Value> {
+        self.get_all(schema, name)?.first()
+    }
+
+    /// Every value of the field
+    pub fn get_all(&self, schema: &Schema, name: &str) -> Option<&[Value]> {
+        self.values.get(schema.field_index(name)?).map(Vec::as_slice)
+    }
+
+    /// Replaces the value of a field that holds a single value, see [`Row::set_all`]
+    pub fn set(&mut self, schema: &Schema, name: &str, value: Value) -> Result<(), CellError> {
+        self.set_all(schema, name, vec![value])
+    }
+
+    /// Replaces every value of the field. The values must match the type and count of the field,
+    /// or the type of the column in the signature of the row when it has one.
+    pub fn set_all(&mut self, schema: &Schema, name: &str, values: Vec<Value>) -> Result<(), CellError> {
+        let index = schema.field_index(name).ok_or_else(|| CellError::UnknownField { field: name.to_string() })?;
+
+        let missing_column = || CellError::MissingColumn { field: name.to_string() };
+        if index >= self.values.len() {
+            return Err(missing_column())
+        }
+
+        // Signatures only exist for T2B rows, which hold a single value per column
+        let field = &schema.fields[index];
+        let (expected, count) = match &self.signature {
+            Some(signature) => (*signature.get(index).ok_or_else(missing_column)?, 1),
+            None => (field.value_type, field.count),
+        };
+
+        if values.len() != count {
+            return Err(CellError::ValueCount { field: name.to_string(), expected: count, found: values.len() })
+        }
+
+        if let Some(value) = values.iter().find(|value| !expected.accepts(value)) {
+            return Err(CellError::ValueType { field: name.to_string(), expected, found: value.clone() })
+        }
+
+        if let Some((expected, found)) = values.iter().find_map(|value| field.size_mismatch(value)) {
+            return Err(CellError::ValueSize { field: name.to_string(), expected, found })
+        }
+
+        self.values[index] = values;
+        Ok(())
+    }
+}
+
+impl Value {
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_u8(&self) -> Option<u8> {
+        match self {
+            Value::Byte(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_i16(&self) -> Option<i16> {
+        match self {
+            Value::Short(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_u16(&self) -> Option<u16> {
+        match self {
+            Value::UShort(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_i32(&self) -> Option<i32> {
+        match self {
+            Value::Int(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Reads both 4 and 8 byte integers
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::Int(v) => Some(*v as i64),
+            Value::Long(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Reads unsigned integers and hashes
+    pub fn as_u32(&self) -> Option<u32> {
+        match self {
+            Value::UInt(v) | Value::Hash(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_hash(&self) -> Option<u32> {
+        match self {
+            Value::Hash(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            Value::Float(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Reads both 4 and 8 byte floats
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Float(v) => Some(*v as f64),
+            Value::FloatLong(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// The text of a string value. Null strings and strings kept as raw bytes have none.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Value::Bytes(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_composite(&self) -> Option<&CompositeValue> {
+        match self {
+            Value::Composite(v) => Some(v),
+            _ => None,
+        }
+    }
+}
\ No newline at end of file
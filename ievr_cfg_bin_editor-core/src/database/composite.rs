@@ -0,0 +1,287 @@
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{common::invalid_data, rdbn::RdbnFieldType};
+
+use super::{Database, Value, ValueType};
+
+/// Layouts of the composite RDBN values (`AbilityData`, `EnhanceData` and `StatusRate`), which the files store as plain bytes.
+///
+/// A layout applies to the fields of its type whose values have its size. The registry can start empty or with the default
+/// layouts, and can be saved and loaded as JSON so that the layouts can be shared without changing the crate.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CompositeRegistry {
+    layouts: Vec<CompositeLayout>,
+}
+
+impl CompositeRegistry {
+    pub fn new() -> CompositeRegistry {
+        CompositeRegistry::default()
+    }
+
+    /// A registry with a layout of 8 bytes for each composite type: a hash naming what the value is about,
+    /// followed by its level, amount or rate. The values of other sizes are left as bytes.
+    pub fn with_default_layouts() -> CompositeRegistry {
+        let layout = |field_type, fields: [(&str, usize, CompositeFieldKind); 2]| CompositeLayout {
+            field_type,
+            size: 8,
+            fields: fields.into_iter().map(|(name, offset, kind)| CompositeField { name: name.to_string(), offset, kind }).collect(),
+        };
+
+        CompositeRegistry {
+            layouts: vec![
+                layout(RdbnFieldType::AbilityData, [("ability", 0, CompositeFieldKind::Hash), ("level", 4, CompositeFieldKind::Int)]),
+                layout(RdbnFieldType::EnhanceData, [("stat", 0, CompositeFieldKind::Hash), ("amount", 4, CompositeFieldKind::Int)]),
+                layout(RdbnFieldType::StatusRate, [("status", 0, CompositeFieldKind::Hash), ("rate", 4, CompositeFieldKind::Float)]),
+            ],
+        }
+    }
+
+    pub fn from_json(json: &str) -> io::Result<CompositeRegistry> {
+        let layouts: Vec<CompositeLayout> = serde_json::from_str(json).map_err(|error| invalid_data(format!("Invalid composite layouts: {error}")))?;
+
+        let mut registry = CompositeRegistry::new();
+        for layout in layouts {
+            registry.register(layout)?;
+        }
+
+        Ok(registry)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.layouts).unwrap()
+    }
+
+    /// Adds a layout, replacing the one for the same type and size
+    pub fn register(&mut self, layout: CompositeLayout) -> io::Result<()> {
+        layout.check()?;
+
+        match self.layouts.iter_mut().find(|known| known.field_type == layout.field_type && known.size == layout.size) {
+            Some(known) => *known = layout,
+            None => self.layouts.push(layout),
+        }
+
+        Ok(())
+    }
+
+    pub fn layout(&self, field_type: RdbnFieldType, size: usize) -> Option<&CompositeLayout> {
+        self.layouts.iter().find(|layout| layout.field_type == field_type && layout.size == size)
+    }
+
+    pub fn layouts(&self) -> &[CompositeLayout] {
+        &self.layouts
+    }
+}
+
+/// The sub-fields of a composite value of `size` bytes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompositeLayout {
+    pub field_type: RdbnFieldType,
+    pub size: usize,
+    pub fields: Vec<CompositeField>,
+}
+
+impl CompositeLayout {
+    /// Checks that the layout describes a composite type and that every sub-field lies within its size
+    fn check(&self) -> io::Result<()> {
+        if !matches!(self.field_type, RdbnFieldType::AbilityData | RdbnFieldType::EnhanceData | RdbnFieldType::StatusRate) {
+            return Err(invalid_data(format!("{:?} is not a composite field type", self.field_type)))
+        }
+
+        for field in &self.fields {
+            if field.end().is_none_or(|end| end > self.size) {
+                return Err(invalid_data(format!("Sub-field {} of the {:?} layout ends after its {} bytes", field.name, self.field_type, self.size)))
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Splits the bytes of a value into its sub-fields, or returns `None` if they don't have the size of the layout
+    /// or a sub-field lies outside of them
+    pub fn decode(&self, bytes: &[u8]) -> Option<CompositeValue> {
+        if bytes.len() != self.size {
+            return None
+        }
+
+        let members = self.fields.iter().map(|field| Some(CompositeMember {
+            name: field.name.clone(),
+            offset: field.offset,
+            kind: field.kind,
+            value: field.kind.decode(bytes.get(field.offset..field.end()?)?),
+        })).collect::<Option<_>>()?;
+
+        Some(CompositeValue { members, raw: bytes.to_vec() })
+    }
+}
+
+/// A sub-field of a composite layout
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompositeField {
+    pub name: String,
+    /// Where the sub-field starts in the value
+    pub offset: usize,
+    pub kind: CompositeFieldKind,
+}
+
+impl CompositeField {
+    /// Where the sub-field ends in the value, or `None` if it is past the addressable range
+    fn end(&self) -> Option<usize> {
+        self.offset.checked_add(self.kind.size())
+    }
+}
+
+/// The little-endian types that the sub-fields can have
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompositeFieldKind {
+    /// A single byte, `0` or `1`
+    Bool,
+    Byte,
+    Short,
+    Int,
+    UInt,
+    Float,
+    Hash,
+}
+
+impl CompositeFieldKind {
+    pub fn size(self) -> usize {
+        match self {
+            CompositeFieldKind::Bool | CompositeFieldKind::Byte => 1,
+            CompositeFieldKind::Short => 2,
+            CompositeFieldKind::Int | CompositeFieldKind::UInt | CompositeFieldKind::Float | CompositeFieldKind::Hash => 4,
+        }
+    }
+
+    fn decode(self, bytes: &[u8]) -> Value {
+        match self {
+            CompositeFieldKind::Bool => Value::Bool(bytes[0] != 0),
+            CompositeFieldKind::Byte => Value::Byte(bytes[0]),
+            CompositeFieldKind::Short => Value::Short(i16::from_le_bytes(bytes.try_into().unwrap())),
+            CompositeFieldKind::Int => Value::Int(i32::from_le_bytes(bytes.try_into().unwrap())),
+            CompositeFieldKind::UInt => Value::UInt(u32::from_le_bytes(bytes.try_into().unwrap())),
+            CompositeFieldKind::Float => Value::Float(f32::from_le_bytes(bytes.try_into().unwrap())),
+            CompositeFieldKind::Hash => Value::Hash(u32::from_le_bytes(bytes.try_into().unwrap())),
+        }
+    }
+
+    fn encode(self, value: &Value) -> Option<Vec<u8>> {
+        let bytes = match (self, value) {
+            (CompositeFieldKind::Bool, Value::Bool(v)) => vec![*v as u8],
+            (CompositeFieldKind::Byte, Value::Byte(v)) => vec![*v],
+            (CompositeFieldKind::Short, Value::Short(v)) => v.to_le_bytes().to_vec(),
+            (CompositeFieldKind::Int, Value::Int(v)) => v.to_le_bytes().to_vec(),
+            (CompositeFieldKind::UInt, Value::UInt(v)) | (CompositeFieldKind::Hash, Value::Hash(v)) => v.to_le_bytes().to_vec(),
+            (CompositeFieldKind::Float, Value::Float(v)) => v.to_le_bytes().to_vec(),
+            _ => return None,
+        };
+
+        Some(bytes)
+    }
+}
+
+/// A composite value split into named sub-fields. Only the values of the sub-fields can be edited,
+/// their place in the bytes comes from the layout.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompositeValue {
+    #[serde(rename = "fields")]
+    members: Vec<CompositeMember>,
+    /// The bytes the value was decoded from. The sub-fields are written over them, which keeps the bytes that no sub-field covers.
+    raw: Vec<u8>,
+}
+
+impl CompositeValue {
+    pub fn members(&self) -> &[CompositeMember] {
+        &self.members
+    }
+
+    pub fn raw(&self) -> &[u8] {
+        &self.raw
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.members.iter().find(|member| member.name == name).map(|member| &member.value)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Value> {
+        self.members.iter_mut().find(|member| member.name == name).map(|member| &mut member.value)
+    }
+
+    /// Encodes the value back into as many bytes as it was decoded from. The sub-fields that still hold the value
+    /// they were decoded to keep their bytes, such as a `Bool` stored as `7`.
+    /// Returns `None` if a sub-field holds a value of another kind or lies outside of the bytes,
+    /// which a value imported from JSON can do.
+    pub fn to_bytes(&self) -> Option<Vec<u8>> {
+        let mut bytes = self.raw.clone();
+
+        for member in &self.members {
+            let value_bytes = member.kind.encode(&member.value)?;
+            let target = bytes.get_mut(member.offset..member.offset.checked_add(value_bytes.len())?)?;
+
+            if member.kind.decode(target) != member.value {
+                target.copy_from_slice(&value_bytes);
+            }
+        }
+
+        Some(bytes)
+    }
+}
+
+/// A sub-field of a composite value
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompositeMember {
+    name: String,
+    offset: usize,
+    kind: CompositeFieldKind,
+    value: Value,
+}
+
+impl CompositeMember {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn kind(&self) -> CompositeFieldKind {
+        self.kind
+    }
+
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    pub fn value_mut(&mut self) -> &mut Value {
+        &mut self.value
+    }
+}
+
+impl Database {
+    /// Splits the composite values that have a layout in `registry` into their sub-fields.
+    /// They are encoded back into bytes when the database is written. Returns how many values were decoded.
+    pub fn decode_composites(&mut self, registry: &CompositeRegistry) -> usize {
+        let mut count = 0;
+
+        for table in &mut self.tables {
+            for (i, field) in table.schema.fields.iter().enumerate() {
+                let ValueType::Rdbn(field_type) = field.value_type else {
+                    continue
+                };
+
+                for row in &mut table.rows {
+                    for value in row.values.get_mut(i).into_iter().flatten() {
+                        if let Value::Bytes(bytes) = value && let Some(decoded) = registry.layout(field_type, bytes.len()).and_then(|layout| layout.decode(bytes)) {
+                            *value = Value::Composite(decoded);
+                            count += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        count
+    }
+}
\ No newline at end of file
@@ -0,0 +1,87 @@
+//! Serde helpers for the floats of the JSON export. JSON has no number for the infinities and NaN,
+//! so those are written as a string of their bits, which also keeps the payload of the NaNs.
+
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+/// A float as it is written in the JSON export
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum JsonFloat<T> {
+    Number(T),
+    /// The bits of a float that is not finite, as `0x` and their hexadecimal value
+    Bits(String),
+}
+
+pub(super) trait Float: Copy + Serialize + for<'de> Deserialize<'de> {
+    fn is_finite(self) -> bool;
+    fn to_bit_string(self) -> String;
+    fn from_bit_string(bits: &str) -> Option<Self>;
+}
+
+impl Float for f32 {
+    fn is_finite(self) -> bool {
+        f32::is_finite(self)
+    }
+
+    fn to_bit_string(self) -> String {
+        format!("0x{:08X}", self.to_bits())
+    }
+
+    fn from_bit_string(bits: &str) -> Option<Self> {
+        u32::from_str_radix(bits.strip_prefix("0x")?, 16).ok().map(f32::from_bits)
+    }
+}
+
+impl Float for f64 {
+    fn is_finite(self) -> bool {
+        f64::is_finite(self)
+    }
+
+    fn to_bit_string(self) -> String {
+        format!("0x{:016X}", self.to_bits())
+    }
+
+    fn from_bit_string(bits: &str) -> Option<Self> {
+        u64::from_str_radix(bits.strip_prefix("0x")?, 16).ok().map(f64::from_bits)
+    }
+}
+
+impl<T: Float> JsonFloat<T> {
+    fn new(value: T) -> JsonFloat<T> {
+        if value.is_finite() { JsonFloat::Number(value) } else { JsonFloat::Bits(value.to_bit_string()) }
+    }
+
+    fn into_float<E: Error>(self) -> Result<T, E> {
+        match self {
+            JsonFloat::Number(value) => Ok(value),
+            JsonFloat::Bits(bits) => T::from_bit_string(&bits).ok_or_else(|| E::custom(format!("invalid float bits {bits:?}"))),
+        }
+    }
+}
+
+pub(super) fn serialize<T: Float, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    JsonFloat::new(*value).serialize(serializer)
+}
+
+pub(super) fn deserialize<'de, T: Float, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+    JsonFloat::deserialize(deserializer)?.into_float()
+}
+
+/// The same for arrays of floats
+pub(super) mod array {
+    use super::*;
+
+    pub(in super::super) fn serialize<T: Float, S: Serializer, const N: usize>(values: &[T; N], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(values.iter().map(|value| JsonFloat::new(*value)))
+    }
+
+    pub(in super::super) fn deserialize<'de, T: Float, D: Deserializer<'de>, const N: usize>(deserializer: D) -> Result<[T; N], D::Error> {
+        let values = Vec::<JsonFloat<T>>::deserialize(deserializer)?
+            .into_iter()
+            .map(JsonFloat::into_float)
+            .collect::<Result<Vec<_>, _>>()?;
+        let count = values.len();
+
+        values.try_into().map_err(|_| D::Error::invalid_length(count, &format!("{N} floats").as_str()))
+    }
+}
\ No newline at end of file
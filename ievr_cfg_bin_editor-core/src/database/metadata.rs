@@ -0,0 +1,80 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    rdbn::{RdbnFieldTypeCategory, RdbnOpaqueHeader, RdbnString, RdbnTypeDeclaration},
+    t2b::{HashType, T2bEncoding, T2bOpaqueFields, ValueLength},
+};
+
+/// The details of the binary format that the tables don't show.
+/// They are used to encode the database with the same layout as the file it was read from.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum FormatMetadata {
+    /// Nothing is known about the source file, the writer picks the layout
+    #[default]
+    None,
+    Rdbn(RdbnMetadata),
+    T2b(T2bMetadata),
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct RdbnMetadata {
+    pub header: RdbnOpaqueHeader,
+    /// The string table of the file, which is written back with the same order and hashes
+    #[serde(default)]
+    pub strings: Vec<RdbnString>,
+    /// The names that were found in a dictionary, which are written with their hash and without a string
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub dictionary_names: BTreeMap<u32, String>,
+    /// The types that no list uses, by their index in the type table. No table shows them, they are written back at that index.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub unused_types: BTreeMap<usize, RdbnTypeDeclaration>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct T2bMetadata {
+    pub encoding: T2bEncoding,
+    pub value_length: ValueLength,
+    pub hash_type: HashType,
+    pub opaque: T2bOpaqueFields,
+    /// The names that were found in a dictionary, which are written with their checksum and without a string
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub dictionary_names: BTreeMap<u32, String>,
+}
+
+impl Default for T2bMetadata {
+    fn default() -> Self {
+        T2bMetadata {
+            encoding: T2bEncoding::default(),
+            value_length: ValueLength::Int,
+            hash_type: HashType::Crc32Standard,
+            opaque: T2bOpaqueFields::default(),
+            dictionary_names: BTreeMap::new(),
+        }
+    }
+}
+
+/// The RDBN type details of a schema
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchemaMetadata {
+    pub unk_hash: u32,
+    /// The size of a row in bytes
+    pub size: i32,
+    /// Where the type is in the type table of the file
+    #[serde(default)]
+    pub type_index: Option<usize>,
+    /// Where the fields of the type start in the field table of the file
+    #[serde(default)]
+    pub field_index: Option<usize>,
+}
+
+/// The RDBN layout of a field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldMetadata {
+    /// The size of one value in bytes
+    pub size: i32,
+    /// Where the field starts in the row
+    pub offset: i32,
+    pub category: RdbnFieldTypeCategory,
+}
\ No newline at end of file
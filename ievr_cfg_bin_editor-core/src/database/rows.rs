@@ -0,0 +1,83 @@
+use crate::{rdbn::RdbnFieldType, t2b::T2bValueType};
+
+use super::{Field, Row, RowMismatch, RowMismatchKind, Table, Value, ValueType};
+
+impl Table {
+    /// A row holding the zero value of every field: `0`, `false`, empty strings, and zeroed bytes of the field size
+    /// for the composite and unknown types
+    pub fn default_row(&self) -> Row {
+        Row {
+            values: self.schema.fields.iter().map(Field::default_values).collect(),
+            position: None,
+            signature: None,
+        }
+    }
+
+    /// Adds a row at the end of the table, see [`Table::insert_row`]
+    pub fn push_row(&mut self, row: Row) -> Result<(), RowMismatch> {
+        self.insert_row(self.rows.len(), row)
+    }
+
+    /// Inserts a row at `index` after checking it against the schema of the table, or its own type signature
+    /// when it has one. Rows without a position are written after the previous row of the table in T2B files.
+    /// Fails with [`RowMismatchKind::OutOfRange`] if `index` is greater than the number of rows.
+    pub fn insert_row(&mut self, index: usize, row: Row) -> Result<(), RowMismatch> {
+        if index > self.rows.len() {
+            return Err(RowMismatch { table: self.name.clone(), row: index, kind: RowMismatchKind::OutOfRange { rows: self.rows.len() } })
+        }
+
+        if let Some(mismatch) = self.check_row(index, &row).into_iter().next() {
+            return Err(mismatch)
+        }
+
+        self.rows.insert(index, row);
+        Ok(())
+    }
+
+    /// Removes the row at `index`, or returns `None` if there is none
+    pub fn remove_row(&mut self, index: usize) -> Option<Row> {
+        (index < self.rows.len()).then(|| self.rows.remove(index))
+    }
+
+    /// Inserts a copy of the row at `index` right after it and returns the copy, or `None` if there is no such row.
+    /// The copy has no position, so T2B files also store it right after the original.
+    pub fn duplicate_row(&mut self, index: usize) -> Option<&mut Row> {
+        let mut row = self.rows.get(index)?.clone();
+        row.position = None;
+
+        self.rows.insert(index + 1, row);
+        Some(&mut self.rows[index + 1])
+    }
+}
+
+impl Field {
+    /// `count` zero values of the type of the field
+    fn default_values(&self) -> Vec<Value> {
+        let value = match self.value_type {
+            ValueType::Rdbn(RdbnFieldType::Bool) => Value::Bool(false),
+            ValueType::Rdbn(RdbnFieldType::Byte) => Value::Byte(0),
+            ValueType::Rdbn(RdbnFieldType::Short | RdbnFieldType::ActType) => Value::Short(0),
+            ValueType::Rdbn(RdbnFieldType::Int | RdbnFieldType::Flag) => Value::Int(0),
+            ValueType::Rdbn(RdbnFieldType::Long) => Value::Long(0),
+            ValueType::Rdbn(RdbnFieldType::UInt) => Value::UInt(0),
+            ValueType::Rdbn(RdbnFieldType::UShort) => Value::UShort(0),
+            ValueType::Rdbn(RdbnFieldType::Float) => Value::Float(0.0),
+            ValueType::Rdbn(RdbnFieldType::Double) => Value::FloatLong(0.0),
+            ValueType::Rdbn(RdbnFieldType::Float2) => Value::Vec2F32([0.0; 2]),
+            ValueType::Rdbn(RdbnFieldType::Float3) => Value::Vec3F32([0.0; 3]),
+            ValueType::Rdbn(RdbnFieldType::Int2) => Value::Tuple2I32(0, 0),
+            ValueType::Rdbn(RdbnFieldType::Hash) => Value::Hash(0),
+            ValueType::Rdbn(RdbnFieldType::RateMatrix | RdbnFieldType::Position) => Value::Vec4F32([0.0; 4]),
+            ValueType::Rdbn(RdbnFieldType::String) => Value::String(String::new()),
+            ValueType::Rdbn(RdbnFieldType::DataTuple) => Value::Tuple2I16(0, 0),
+            ValueType::Rdbn(RdbnFieldType::AbilityData | RdbnFieldType::EnhanceData | RdbnFieldType::StatusRate | RdbnFieldType::Unknown(_)) =>
+                Value::Bytes(vec![0; self.size().unwrap_or(0) as usize]),
+            ValueType::T2b(T2bValueType::String) => Value::String(String::new()),
+            // No file holds the invalid type, as reading it fails
+            ValueType::T2b(T2bValueType::Integer | T2bValueType::Invalid) => Value::Int(0),
+            ValueType::T2b(T2bValueType::FloatingPoint) => Value::Float(0.0),
+        };
+
+        vec![value; self.count]
+    }
+}
\ No newline at end of file
@@ -1,12 +1,18 @@
 use serde::{Serialize, Deserialize};
 
-use crate::{rdbn::{RdbnFieldType, RdbnValue}, t2b::{T2bEntryValue, T2bValue, T2bValueType}};
+use crate::{rdbn::{RdbnFieldType, RdbnFieldTypeCategory, RdbnValue}, t2b::{T2bEntryValue, T2bValue, T2bValueType}};
 
+use super::{CompositeValue, FieldMetadata, SchemaMetadata};
+
+/// An RDBN list, or the T2B entries that share a name
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Table {
     pub(super) name: String,
     pub(super) schema: Schema,
-    pub(super) rows: Vec<Row>
+    pub(super) rows: Vec<Row>,
+    /// Unknown value of the RDBN root entry, kept to write the file back unchanged
+    #[serde(default)]
+    pub(super) unk1: i16,
 }
 
 impl Table {
@@ -31,52 +37,198 @@ impl Table {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// The columns of a table. For RDBN tables, the name and fields of the type of the list.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct Schema {
     pub(super) name: String,
-    pub(super) fields: Vec<Field>
+    pub(super) fields: Vec<Field>,
+    /// The RDBN type details, `None` for T2B tables
+    #[serde(default)]
+    pub(super) metadata: Option<SchemaMetadata>,
 }
 
 impl Schema {
-    
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn fields(&self) -> &Vec<Field> {
+        &self.fields
+    }
+
+    pub fn metadata(&self) -> Option<&SchemaMetadata> {
+        self.metadata.as_ref()
+    }
+
+    /// The number of leading columns that every row must have
+    pub fn required_columns(&self) -> usize {
+        self.fields.iter().position(|field| field.optional).unwrap_or(self.fields.len())
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// A column of a table
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct Field {
     pub name: String,
     pub value_type: ValueType,
+    /// How many values every row holds in this column
     pub count: usize,
+    /// Rows can end before this column. Only the trailing columns of T2B tables can be optional.
+    #[serde(default)]
+    pub optional: bool,
+    /// The RDBN layout of the field, `None` for T2B fields
+    #[serde(default)]
+    pub metadata: Option<FieldMetadata>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl Field {
+    /// The size of one value in bytes, taken from the RDBN layout or else from the type.
+    /// `None` for T2B fields and for the composite and unknown RDBN types that have no layout yet.
+    pub fn size(&self) -> Option<i32> {
+        match (&self.metadata, self.value_type) {
+            (Some(metadata), _) => Some(metadata.size),
+            (None, ValueType::Rdbn(field_type)) => field_type.size(),
+            (None, ValueType::T2b(_)) => None,
+        }
+    }
+
+    /// Where the field starts in an RDBN row, in bytes. `None` until the layout is known.
+    pub fn offset(&self) -> Option<i32> {
+        self.metadata.as_ref().map(|metadata| metadata.offset)
+    }
+
+    /// The RDBN category of the field, `None` for T2B fields
+    pub fn category(&self) -> Option<RdbnFieldTypeCategory> {
+        match (&self.metadata, self.value_type) {
+            (Some(metadata), _) => Some(metadata.category),
+            (None, ValueType::Rdbn(field_type)) => Some(field_type.into()),
+            (None, ValueType::T2b(_)) => None,
+        }
+    }
+
+    /// The expected and found byte lengths of a composite or unknown value that doesn't have the size of the field.
+    /// These values are stored as they are, so they must have that size.
+    pub(super) fn size_mismatch(&self, value: &Value) -> Option<(usize, usize)> {
+        // The composite and unknown types are the ones without a size of their own
+        if !matches!(self.value_type, ValueType::Rdbn(field_type) if field_type.size().is_none()) {
+            return None
+        }
+        let expected = self.size()? as usize;
+
+        let found = match value {
+            Value::Bytes(bytes) => bytes.len(),
+            Value::Composite(composite) => composite.raw().len(),
+            _ => return None,
+        };
+
+        (found != expected).then_some((expected, found))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Row {
     pub values: Vec<Vec<Value>>, // A single column can store multiple values in the RDBN data format
+    /// Index of the entry in the T2B file. Rows without one are written right after the previous row of their table.
+    #[serde(default)]
+    pub position: Option<usize>,
+    /// The value types of a T2B row that doesn't follow the schema of its table.
+    /// When set, the row is checked against it instead of the schema.
+    #[serde(default)]
+    pub signature: Option<Vec<ValueType>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum ValueType {
     Rdbn(RdbnFieldType),
     T2b(T2bValueType),
 }
 
+impl ValueType {
+    /// Whether a column of this type can hold the value
+    pub fn accepts(&self, value: &Value) -> bool {
+        match self {
+            ValueType::Rdbn(field_type) => value.to_rdbn(*field_type).is_some(),
+            ValueType::T2b(value_type) => value.to_t2b().is_some_and(|entry_value| entry_value.r#type == *value_type),
+        }
+    }
+}
+
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Value {
     Bool(bool),
     Byte(u8),
     Int(i32),
     Long(i64),
     Short(i16),
+    UShort(u16),
     UInt(u32),
-    Float(f32),
-    FloatLong(f64),
+    Float(#[serde(with = "super::json_float")] f32),
+    FloatLong(#[serde(with = "super::json_float")] f64),
     String(String),
+    /// An RDBN string field that points to no string
+    NullString,
+    /// An RDBN string field whose offset is outside of the string table
+    StringOffset(u32),
 
     Hash(u32),
     Bytes(Vec<u8>),
     
     Tuple2I16(i16, i16),
-    Vec4F32([f32; 4]),
+    Tuple2I32(i32, i32),
+    Vec2F32(#[serde(with = "super::json_float::array")] [f32; 2]),
+    Vec3F32(#[serde(with = "super::json_float::array")] [f32; 3]),
+    Vec4F32(#[serde(with = "super::json_float::array")] [f32; 4]),
+
+    /// A composite RDBN value split into sub-fields, see [`CompositeRegistry`](super::CompositeRegistry)
+    Composite(CompositeValue),
+}
+
+impl Value {
+    /// Converts the value to the RDBN representation of the given field type, if it can hold it
+    pub(crate) fn to_rdbn(&self, field_type: RdbnFieldType) -> Option<RdbnValue> {
+        let value = match (field_type, self) {
+            (RdbnFieldType::AbilityData | RdbnFieldType::EnhanceData | RdbnFieldType::StatusRate | RdbnFieldType::Unknown(_), Value::Bytes(v)) => RdbnValue::Bytes(v.clone()),
+            (RdbnFieldType::AbilityData | RdbnFieldType::EnhanceData | RdbnFieldType::StatusRate, Value::Composite(v)) => RdbnValue::Bytes(v.to_bytes()?),
+            (RdbnFieldType::Bool, Value::Bool(v)) => RdbnValue::Bool(*v),
+            (RdbnFieldType::Byte, Value::Byte(v)) => RdbnValue::Byte(*v),
+            (RdbnFieldType::Short | RdbnFieldType::ActType, Value::Short(v)) => RdbnValue::Short(*v),
+            (RdbnFieldType::Int | RdbnFieldType::Flag, Value::Int(v)) => RdbnValue::Int(*v),
+            (RdbnFieldType::Long, Value::Long(v)) => RdbnValue::Long(*v),
+            (RdbnFieldType::UInt, Value::UInt(v)) => RdbnValue::Uint(*v),
+            (RdbnFieldType::UShort, Value::UShort(v)) => RdbnValue::UShort(*v),
+            (RdbnFieldType::Float, Value::Float(v)) => RdbnValue::Float(*v),
+            (RdbnFieldType::Double, Value::FloatLong(v)) => RdbnValue::Double(*v),
+            (RdbnFieldType::Float2, Value::Vec2F32(v)) => RdbnValue::Float2(*v),
+            (RdbnFieldType::Float3, Value::Vec3F32(v)) => RdbnValue::Float3(*v),
+            (RdbnFieldType::Int2, Value::Tuple2I32(a, b)) => RdbnValue::Int2([*a, *b]),
+            (RdbnFieldType::Hash, Value::UInt(v)) => RdbnValue::Uint(*v),
+            (RdbnFieldType::Hash, Value::Hash(v)) => RdbnValue::Hash(*v),
+            (RdbnFieldType::RateMatrix | RdbnFieldType::Position, Value::Vec4F32(v)) => RdbnValue::Float4(*v),
+            (RdbnFieldType::String, Value::String(v)) => RdbnValue::String(v.clone()),
+            (RdbnFieldType::String, Value::NullString) => RdbnValue::NullString,
+            (RdbnFieldType::String, Value::Bytes(v)) => RdbnValue::Bytes(v.clone()),
+            (RdbnFieldType::String, Value::StringOffset(v)) => RdbnValue::StringOffset(*v),
+            (RdbnFieldType::DataTuple, Value::Tuple2I16(a, b)) => RdbnValue::Short2([*a, *b]),
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    /// Converts the value to a T2B entry value, if T2B can store it
+    pub(crate) fn to_t2b(&self) -> Option<T2bEntryValue> {
+        let (r#type, value) = match self {
+            Value::String(v) => (T2bValueType::String, T2bValue::String(v.clone())),
+            Value::Int(v) => (T2bValueType::Integer, T2bValue::Integer(*v)),
+            Value::Long(v) => (T2bValueType::Integer, T2bValue::Long(*v)),
+            Value::Float(v) => (T2bValueType::FloatingPoint, T2bValue::F32(*v)),
+            Value::FloatLong(v) => (T2bValueType::FloatingPoint, T2bValue::F64(*v)),
+            _ => return None,
+        };
+
+        Some(T2bEntryValue { r#type, value })
+    }
 }
 
 impl From<&RdbnValue> for Value {
@@ -86,13 +238,21 @@ impl From<&RdbnValue> for Value {
             RdbnValue::Byte(v) => Value::Byte(*v),
             RdbnValue::Short(v) => Value::Short(*v),
             RdbnValue::Int(v) => Value::Int(*v),
+            RdbnValue::Long(v) => Value::Long(*v),
             RdbnValue::Uint(v) => Value::UInt(*v),
+            RdbnValue::UShort(v) => Value::UShort(*v),
             RdbnValue::Float(v) => Value::Float(*v),
+            RdbnValue::Double(v) => Value::FloatLong(*v),
             RdbnValue::Hash(v) => Value::Hash(*v),
             RdbnValue::String(v) => Value::String(v.clone()),
+            RdbnValue::NullString => Value::NullString,
+            RdbnValue::StringOffset(v) => Value::StringOffset(*v),
             RdbnValue::Bytes(v) => Value::Bytes(v.clone()),
+            RdbnValue::Float2(v) => Value::Vec2F32(*v),
+            RdbnValue::Float3(v) => Value::Vec3F32(*v),
             RdbnValue::Float4(v) => Value::Vec4F32(*v),
             RdbnValue::Short2(v) => Value::Tuple2I16(v[0], v[1]),
+            RdbnValue::Int2(v) => Value::Tuple2I32(v[0], v[1]),
         }
     }
 }
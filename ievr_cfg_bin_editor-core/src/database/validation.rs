@@ -0,0 +1,107 @@
+use std::{error::Error, fmt, io};
+
+use super::{Database, Row, Table, Value, ValueType};
+
+/// A row that doesn't match the schema of its table, or its own type signature
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowMismatch {
+    pub table: String,
+    pub row: usize,
+    pub kind: RowMismatchKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RowMismatchKind {
+    /// The row has `found` columns, but needs between `min` and `max`
+    ColumnCount { min: usize, max: usize, found: usize },
+    /// The column holds `found` values instead of the `expected` count of its field
+    ValueCount { column: usize, expected: usize, found: usize },
+    /// A value of the column doesn't fit its type
+    ValueType { column: usize, expected: ValueType, found: Value },
+    /// A composite or unknown value of the column has `found` bytes instead of the `expected` size of its field
+    ValueSize { column: usize, expected: usize, found: usize },
+    /// The row would be inserted past the end of the table, which has `rows` rows
+    OutOfRange { rows: usize },
+}
+
+impl fmt::Display for RowMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let RowMismatch { table, row, kind } = self;
+
+        match kind {
+            RowMismatchKind::ColumnCount { min, max, found } if min == max =>
+                write!(f, "Row {row} of table {table} has {found} columns instead of {max}"),
+            RowMismatchKind::ColumnCount { min, max, found } =>
+                write!(f, "Row {row} of table {table} has {found} columns instead of {min} to {max}"),
+            RowMismatchKind::ValueCount { column, expected, found } =>
+                write!(f, "Row {row} of table {table}: column {column} holds {found} values instead of {expected}"),
+            RowMismatchKind::ValueType { column, expected, found } =>
+                write!(f, "Row {row} of table {table}: column {column} of type {expected:?} cannot hold {found:?}"),
+            RowMismatchKind::ValueSize { column, expected, found } =>
+                write!(f, "Row {row} of table {table}: column {column} holds values of {expected} bytes, not {found}"),
+            RowMismatchKind::OutOfRange { rows } =>
+                write!(f, "Row {row} of table {table} is out of range, the table has {rows} rows"),
+        }
+    }
+}
+
+impl Error for RowMismatch {}
+
+impl From<RowMismatch> for io::Error {
+    fn from(error: RowMismatch) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, error)
+    }
+}
+
+impl Database {
+    /// Checks every row of every table, see [`Table::validate`]
+    pub fn validate(&self) -> Vec<RowMismatch> {
+        self.tables.iter().flat_map(Table::validate).collect()
+    }
+}
+
+impl Table {
+    /// Checks that every row matches the schema of the table, or its own type signature when it has one.
+    /// Returns every mismatch found, so an empty list means the table can be encoded.
+    pub fn validate(&self) -> Vec<RowMismatch> {
+        self.rows.iter().enumerate().flat_map(|(j, row)| self.check_row(j, row)).collect()
+    }
+
+    /// Checks a single row, reported as row `j` of the table
+    pub(super) fn check_row(&self, j: usize, row: &Row) -> Vec<RowMismatch> {
+        let mut mismatches = Vec::new();
+        let mismatch = |kind| RowMismatch { table: self.name.clone(), row: j, kind };
+
+        let (min, max) = match &row.signature {
+            Some(signature) => (signature.len(), signature.len()),
+            None => (self.schema.required_columns(), self.schema.fields.len()),
+        };
+
+        if row.values.len() < min || row.values.len() > max {
+            mismatches.push(mismatch(RowMismatchKind::ColumnCount { min, max, found: row.values.len() }));
+            return mismatches
+        }
+
+        for (i, values) in row.values.iter().enumerate() {
+            // Signatures only exist for T2B rows, which hold a single value per column
+            let (expected, count) = match &row.signature {
+                Some(signature) => (signature[i], 1),
+                None => (self.schema.fields[i].value_type, self.schema.fields[i].count),
+            };
+
+            if values.len() != count {
+                mismatches.push(mismatch(RowMismatchKind::ValueCount { column: i, expected: count, found: values.len() }));
+            }
+
+            if let Some(value) = values.iter().find(|value| !expected.accepts(value)) {
+                mismatches.push(mismatch(RowMismatchKind::ValueType { column: i, expected, found: value.clone() }));
+            }
+
+            if row.signature.is_none() && let Some((expected, found)) = values.iter().find_map(|value| self.schema.fields[i].size_mismatch(value)) {
+                mismatches.push(mismatch(RowMismatchKind::ValueSize { column: i, expected, found }));
+            }
+        }
+
+        mismatches
+    }
+}
\ No newline at end of file
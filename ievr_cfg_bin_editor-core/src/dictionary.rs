@@ -0,0 +1,167 @@
+use std::{collections::{BTreeMap, HashMap}, io::{self, Write}};
+
+use serde_json::Value as JsonValue;
+
+use crate::common::{
+    invalid_data,
+    hash::{compute_crc32_jam, compute_crc32_standard},
+};
+
+/// Names of CRC32 and CRC32-JAM hashes, used to show the names that a file only stores as hashes.
+///
+/// A dictionary can be loaded from:
+/// - a JSON object mapping hashes to names, with the hashes written in hexadecimal with a `0x` prefix (`"0x1A2B3C4D"`)
+///   or in decimal (`"439041101"`)
+/// - a JSON array of names
+/// - text with one entry per line, either `hash=name` (also separated by a tab, a comma or a space) or a plain name.
+///   The hashes are in hexadecimal, with a `0x` prefix or all 8 digits. Empty lines and lines starting with `#` or `//` are skipped.
+///
+/// Plain names are registered under both their CRC32 and CRC32-JAM hashes.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HashDictionary {
+    names: HashMap<u32, String>,
+}
+
+impl HashDictionary {
+    pub fn new() -> HashDictionary {
+        HashDictionary::default()
+    }
+
+    /// Loads a dictionary in any of the supported formats
+    pub fn load(text: &str) -> io::Result<HashDictionary> {
+        let text = text.trim_start_matches('\u{FEFF}');
+
+        if text.trim_start().starts_with(['{', '[']) {
+            Self::from_json(text)
+        } else {
+            Ok(Self::from_text(text))
+        }
+    }
+
+    /// Reads a JSON object of hashes and names, or a JSON array of names
+    pub fn from_json(json: &str) -> io::Result<HashDictionary> {
+        let value: JsonValue = serde_json::from_str(json).map_err(|error| invalid_data(format!("Invalid dictionary: {error}")))?;
+        let mut dictionary = HashDictionary::new();
+
+        match value {
+            JsonValue::Object(entries) => {
+                for (hash, name) in entries {
+                    let hash = parse_json_hash(&hash).ok_or_else(|| invalid_data(format!("Invalid dictionary hash {hash}")))?;
+                    let JsonValue::String(name) = name else {
+                        return Err(invalid_data(format!("The name of the hash {hash:08X} is not a string")))
+                    };
+
+                    dictionary.insert(hash, name);
+                }
+            },
+            JsonValue::Array(names) => {
+                for name in names {
+                    let JsonValue::String(name) = name else {
+                        return Err(invalid_data(format!("Invalid dictionary name {name}")))
+                    };
+
+                    dictionary.insert_name(&name);
+                }
+            },
+            _ => return Err(invalid_data("A dictionary must be a JSON object or array".to_string())),
+        }
+
+        Ok(dictionary)
+    }
+
+    /// Reads `hash=name` lines and plain word lists
+    pub fn from_text(text: &str) -> HashDictionary {
+        let mut dictionary = HashDictionary::new();
+
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') || line.starts_with("//") {
+                continue;
+            }
+
+            let entry = line.split_once(['=', '\t', ',', ' '])
+                .and_then(|(hash, name)| Some((parse_hash(hash.trim())?, name.trim())));
+
+            match entry {
+                Some((hash, name)) if !name.is_empty() => dictionary.insert(hash, name.to_string()),
+                _ => dictionary.insert_name(line),
+            }
+        }
+
+        dictionary
+    }
+
+    /// Names a hash, replacing its previous name
+    pub fn insert(&mut self, hash: u32, name: String) {
+        self.names.insert(hash, name);
+    }
+
+    /// Registers a name under its CRC32 and CRC32-JAM hashes
+    pub fn insert_name(&mut self, name: &str) {
+        self.names.insert(compute_crc32_standard(name.as_bytes()), name.to_string());
+        self.names.insert(compute_crc32_jam(name.as_bytes()), name.to_string());
+    }
+
+    pub fn get(&self, hash: u32) -> Option<&str> {
+        self.names.get(&hash).map(String::as_str)
+    }
+
+    /// Adds the entries of `other`. The names already in this dictionary are kept.
+    pub fn merge(&mut self, other: &HashDictionary) {
+        for (hash, name) in &other.names {
+            self.names.entry(*hash).or_insert_with(|| name.clone());
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &str)> {
+        self.names.iter().map(|(hash, name)| (*hash, name.as_str()))
+    }
+
+    /// Writes the dictionary as `hash=name` lines, sorted by hash
+    pub fn to_text(&self) -> String {
+        self.sorted().into_iter()
+            .map(|(hash, name)| format!("{hash:08X}={name}\n"))
+            .collect()
+    }
+
+    /// Writes the dictionary as a JSON object, sorted by hash
+    pub fn to_json(&self) -> String {
+        let entries: BTreeMap<String, &str> = self.sorted().into_iter()
+            .map(|(hash, name)| (format!("0x{hash:08X}"), name))
+            .collect();
+
+        serde_json::to_string_pretty(&entries).unwrap()
+    }
+
+    pub fn write_to<W: Write>(&self, output: &mut W) -> io::Result<()> {
+        output.write_all(self.to_text().as_bytes())
+    }
+
+    fn sorted(&self) -> BTreeMap<u32, &str> {
+        self.iter().collect()
+    }
+}
+
+/// Parses a hash written in hexadecimal, either with `0x` or with all 8 digits
+fn parse_hash(text: &str) -> Option<u32> {
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None if text.len() == 8 => u32::from_str_radix(text, 16).ok(),
+        None => None,
+    }
+}
+
+/// Parses a JSON key, which is in hexadecimal with `0x` and in decimal otherwise
+fn parse_json_hash(text: &str) -> Option<u32> {
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
\ No newline at end of file
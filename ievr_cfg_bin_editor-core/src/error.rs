@@ -0,0 +1,111 @@
+use std::{error::Error, fmt, io};
+
+/// An error encountered while parsing a cfg.bin file.
+///
+/// Apart from [`CfgBinError::UnknownFormat`], every error carries the byte offset in the file
+/// and the name of the section where the problem was found.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CfgBinError {
+    /// The file is neither an RDBN nor a T2B file
+    UnknownFormat,
+    /// The magic number doesn't match the expected format
+    BadMagic { offset: usize, section: &'static str, magic: u32 },
+    /// The section needs `size` bytes starting at `offset`, but the file ends before
+    Truncated { offset: usize, section: &'static str, size: usize },
+    /// An offset or count read from the file points outside of it
+    InvalidOffset { offset: usize, section: &'static str, value: i64 },
+    /// An index read from the file refers to an entry that doesn't exist
+    IndexOutOfRange { offset: usize, section: &'static str, index: i64, count: usize },
+    /// The RDBN field type category is not known
+    UnknownFieldCategory { offset: usize, section: &'static str, category: i16 },
+    /// A T2B value uses the reserved value type 3
+    InvalidValueType { offset: usize, section: &'static str },
+    /// The T2B entries fit neither 4 nor 8 byte values
+    UndetectableValueLength { offset: usize, section: &'static str },
+    /// A string offset points outside of its string table
+    StringOffsetOutOfRange { offset: usize, section: &'static str, string_offset: i64 },
+    /// A string is not valid in the file encoding
+    InvalidString { offset: usize, section: &'static str, encoding: &'static str },
+    /// A name hash has no matching string
+    UnknownHash { offset: usize, section: &'static str, hash: u32 },
+    /// A checksum doesn't match its string with any supported hash algorithm
+    HashMismatch { offset: usize, section: &'static str, hash: u32 },
+}
+
+impl CfgBinError {
+    /// The byte offset in the file where the error was found
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            CfgBinError::UnknownFormat => None,
+            CfgBinError::BadMagic { offset, .. }
+            | CfgBinError::Truncated { offset, .. }
+            | CfgBinError::InvalidOffset { offset, .. }
+            | CfgBinError::IndexOutOfRange { offset, .. }
+            | CfgBinError::UnknownFieldCategory { offset, .. }
+            | CfgBinError::InvalidValueType { offset, .. }
+            | CfgBinError::UndetectableValueLength { offset, .. }
+            | CfgBinError::StringOffsetOutOfRange { offset, .. }
+            | CfgBinError::InvalidString { offset, .. }
+            | CfgBinError::UnknownHash { offset, .. }
+            | CfgBinError::HashMismatch { offset, .. } => Some(*offset),
+        }
+    }
+
+    /// The name of the section where the error was found
+    pub fn section(&self) -> Option<&'static str> {
+        match self {
+            CfgBinError::UnknownFormat => None,
+            CfgBinError::BadMagic { section, .. }
+            | CfgBinError::Truncated { section, .. }
+            | CfgBinError::InvalidOffset { section, .. }
+            | CfgBinError::IndexOutOfRange { section, .. }
+            | CfgBinError::UnknownFieldCategory { section, .. }
+            | CfgBinError::InvalidValueType { section, .. }
+            | CfgBinError::UndetectableValueLength { section, .. }
+            | CfgBinError::StringOffsetOutOfRange { section, .. }
+            | CfgBinError::InvalidString { section, .. }
+            | CfgBinError::UnknownHash { section, .. }
+            | CfgBinError::HashMismatch { section, .. } => Some(section),
+        }
+    }
+}
+
+impl fmt::Display for CfgBinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CfgBinError::UnknownFormat => write!(f, "Unable to detect file format"),
+            CfgBinError::BadMagic { offset, section, magic } =>
+                write!(f, "{section} at {offset:#X}: invalid magic {magic:#010X}"),
+            CfgBinError::Truncated { offset, section, size } =>
+                write!(f, "{section} at {offset:#X}: the file ends before the {size:#X} bytes of the section"),
+            CfgBinError::InvalidOffset { offset, section, value } if *value < 0 =>
+                write!(f, "{section} at {offset:#X}: -{:#X} points outside of the file", value.unsigned_abs()),
+            CfgBinError::InvalidOffset { offset, section, value } =>
+                write!(f, "{section} at {offset:#X}: {value:#X} points outside of the file"),
+            CfgBinError::IndexOutOfRange { offset, section, index, count } =>
+                write!(f, "{section} at {offset:#X}: index {index} is out of range, there are only {count} entries"),
+            CfgBinError::UnknownFieldCategory { offset, section, category } =>
+                write!(f, "{section} at {offset:#X}: unknown field type category {category:#X}"),
+            CfgBinError::InvalidValueType { offset, section } =>
+                write!(f, "{section} at {offset:#X}: invalid value type"),
+            CfgBinError::UndetectableValueLength { offset, section } =>
+                write!(f, "{section} at {offset:#X}: the entries fit neither 4 nor 8 byte values"),
+            CfgBinError::StringOffsetOutOfRange { offset, section, string_offset } =>
+                write!(f, "{section} at {offset:#X}: string offset {string_offset:#X} is out of range"),
+            CfgBinError::InvalidString { offset, section, encoding } =>
+                write!(f, "{section} at {offset:#X}: invalid {encoding} string"),
+            CfgBinError::UnknownHash { offset, section, hash } =>
+                write!(f, "{section} at {offset:#X}: no string matches the hash {hash:#010X}"),
+            CfgBinError::HashMismatch { offset, section, hash } =>
+                write!(f, "{section} at {offset:#X}: the checksum {hash:#010X} doesn't match its string"),
+        }
+    }
+}
+
+impl Error for CfgBinError {}
+
+impl From<CfgBinError> for io::Error {
+    fn from(error: CfgBinError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, error)
+    }
+}
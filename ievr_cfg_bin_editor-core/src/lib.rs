@@ -2,21 +2,53 @@ mod rdbn;
 mod t2b;
 mod database;
 mod common;
+mod error;
+mod dictionary;
 
-use crate::{
-    rdbn::Rdbn, t2b::T2b
+pub use database::{
+    Database, Value, Table, Row, Schema, Field, ValueType,
+    FormatMetadata, RdbnMetadata, T2bMetadata, SchemaMetadata, FieldMetadata,
+    RowMismatch, RowMismatchKind, CellError,
+    CompositeRegistry, CompositeLayout, CompositeField, CompositeFieldKind, CompositeValue, CompositeMember,
 };
 
-pub use database::{
-    Database, Value, Table, Row
+pub use error::CfgBinError;
+
+pub use dictionary::HashDictionary;
+
+pub use rdbn::{
+    Rdbn, RdbnBuilder, RdbnTypeDeclaration, RdbnFieldDeclaration, RdbnListEntry, RdbnValue,
+    RdbnString, StringDecoding, RdbnOpaqueHeader, RdbnFieldType, RdbnFieldTypeCategory,
 };
 
-pub fn parse_database(file: &[u8]) -> std::io::Result<Database> {
-    match Rdbn::read(file) {
-        Some(rdbn) => Ok(rdbn.into()),
-        None => match T2b::read(file) {
-            Some(t2b) => Ok(t2b.into()),
-            None => panic!("Unable to detect file format")
+pub use t2b::{
+    T2b, T2bBuilder, T2bEntry, T2bEntryValue, T2bValue, T2bTree, T2bNode, T2bBlock,
+    T2bEncoding, T2bOpaqueFields, T2bValueType, HashType, ValueLength,
+};
+
+/// Parses an RDBN or T2B file. RDBN strings that are not valid UTF-8 are kept as raw bytes.
+pub fn parse_database(file: &[u8]) -> Result<Database, CfgBinError> {
+    parse_database_with_decoding(file, StringDecoding::default())
+}
+
+/// Parses an RDBN or T2B file, reading the RDBN strings that are not valid UTF-8 according to `decoding`
+pub fn parse_database_with_decoding(file: &[u8], decoding: StringDecoding) -> Result<Database, CfgBinError> {
+    parse_database_with_dictionary(file, decoding, &HashDictionary::new())
+}
+
+/// Parses an RDBN or T2B file like [`parse_database_with_decoding`], and names the hashes that the file has no string for
+/// with `dictionary`, both the names of the tables and fields and the hash values
+pub fn parse_database_with_dictionary(file: &[u8], decoding: StringDecoding, dictionary: &HashDictionary) -> Result<Database, CfgBinError> {
+    let mut database: Database = if file.starts_with(b"RDBN") {
+        Rdbn::read_with_dictionary(file, decoding, dictionary)?.into()
+    } else {
+        match T2b::read_with_dictionary(file, dictionary) {
+            Ok(t2b) => t2b.into(),
+            Err(CfgBinError::BadMagic { .. }) => return Err(CfgBinError::UnknownFormat),
+            Err(error) => return Err(error),
         }
-    }
+    };
+
+    database.resolve_hashes(dictionary);
+    Ok(database)
 }
\ No newline at end of file
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{collections::{BTreeMap, HashMap}, io::{self, Write}};
 
 mod header;
 mod root_entry;
@@ -9,280 +9,705 @@ mod field_type;
 mod field_type_category;
 mod declarations;
 mod list_entry;
+mod string_decoding;
+mod string_table;
+mod editing;
+mod builder;
 
 use self::{
     header::RdbnHeader,
     root_entry::RdbnRootEntry,
     type_entry::RdbnTypeEntry,
     field_entry::RdbnFieldEntry,
-    declarations::{RdbnFieldDeclaration, RdbnTypeDeclaration},
-    field_type_category::RdbnFieldTypeCategory,
-    list_entry::RdbnListEntry,
+    string_table::StringTableWriter,
 };
 
+pub use header::RdbnOpaqueHeader;
+pub use declarations::{RdbnFieldDeclaration, RdbnTypeDeclaration};
 pub use field_type::RdbnFieldType;
-pub use list_entry::RdbnValue;
-
-use super::common::binary_reader::BinaryReader;
+pub use field_type_category::RdbnFieldTypeCategory;
+pub use list_entry::{RdbnListEntry, RdbnValue};
+pub use string_decoding::StringDecoding;
+pub use string_table::RdbnString;
+pub use builder::RdbnBuilder;
+
+use super::error::CfgBinError;
+use super::dictionary::HashDictionary;
+use super::common::{
+    invalid_data,
+    binary_reader::BinaryReader,
+    binary_writer::BinaryWriter,
+    string_pool::StringPool,
+};
 
 const MINIMUM_SIZE: usize = 0x3C;
 const RDBN_HEADER: u32 = const { u32::from_le_bytes(*b"RDBN") };
+const DATA_OFFSET: usize = 0x40;
+const ENTRY_ALIGNMENT: usize = 0x20;
+/// The value of a string field that points to no string
+const NULL_STRING_OFFSET: u32 = u32::MAX;
+/// The most entries a table of the file can hold, since the header stores the counts as `i16`
+pub(crate) const MAX_ENTRY_COUNT: usize = i16::MAX as usize;
+
+/// Where the sections of a file being read start
+struct RdbnLayout {
+    type_position: usize,
+    field_position: usize,
+    root_position: usize,
+    value_offset: usize,
+    string_offset: usize,
+}
 
+/// An RDBN file: a table of types, each a list of fields, and lists of rows that each use one of the types.
+///
+/// This is the low-level view of the file. [`Database`](crate::Database) gives a format-independent view of the same data.
+#[derive(Debug, Clone)]
 pub struct Rdbn {
+    pub header: RdbnOpaqueHeader,
+    /// The type table, in the order of the file
     pub types: Vec<RdbnTypeDeclaration>,
     pub lists: Vec<RdbnListEntry>,
+    /// The string table, in the order of the file. The writer keeps its entries and their hashes,
+    /// and only adds the names of the types, fields and lists that are not in there.
+    pub string_lookup: Vec<RdbnString>,
+    /// The names that the file has no string for, found in a dictionary when reading.
+    /// The writer keeps their hash and doesn't add them to the string table.
+    pub dictionary_names: BTreeMap<u32, String>,
 }
 
 impl Rdbn {
-    pub fn read(file: &[u8]) -> Option<Rdbn> {
-        if file.len() < MINIMUM_SIZE {
-            return None
-        }
+    /// Reads an RDBN file, keeping the strings that are not valid UTF-8 as raw bytes
+    pub fn read(file: &[u8]) -> Result<Rdbn, CfgBinError> {
+        Self::read_with_decoding(file, StringDecoding::default())
+    }
+
+    /// Reads an RDBN file, handling the strings that are not valid UTF-8 according to `decoding`
+    pub fn read_with_decoding(file: &[u8], decoding: StringDecoding) -> Result<Rdbn, CfgBinError> {
+        Self::read_with_dictionary(file, decoding, &HashDictionary::new())
+    }
 
+    /// Reads an RDBN file, naming the types, fields and lists whose hash has no string in the file with `dictionary`
+    pub fn read_with_dictionary(file: &[u8], decoding: StringDecoding, dictionary: &HashDictionary) -> Result<Rdbn, CfgBinError> {
         let mut binary_reader = BinaryReader::new(file);
 
-        let header = RdbnHeader::new(&mut binary_reader);
+        let header = RdbnHeader::new(&mut binary_reader.window(0, MINIMUM_SIZE, "RDBN header")?)?;
 
         if header.magic != RDBN_HEADER {
-            return None;
+            return Err(CfgBinError::BadMagic { offset: 0, section: "RDBN header", magic: header.magic });
         }
 
-        let data_offset = header.data_offset << 2;
+        let data_offset = (header.data_offset as i64) << 2;
+        let section_position = |header_offset: usize, offset: i64| -> Result<usize, CfgBinError> {
+            usize::try_from(offset + data_offset)
+                .ok()
+                .filter(|position| *position <= file.len())
+                .ok_or(CfgBinError::InvalidOffset { offset: header_offset, section: "RDBN header", value: offset + data_offset })
+        };
 
         // Read root entries
-        let position = (header.root_offset << 2) + data_offset; debug_assert!(position > 0);
-        binary_reader.set_position(position as usize);
-        let root_entries = Self::read_root_entries(&mut binary_reader, header.root_count);
+        let root_position = section_position(0x2C, (header.root_offset as i64) << 2)?;
+        let root_entries = Self::read_root_entries(&binary_reader, root_position, header.root_count)?;
 
         // Read type entries
-        let position = (header.type_offset << 2) + data_offset; debug_assert!(position > 0);
-        binary_reader.set_position(position as usize);
-        let type_entries = Self::read_type_entries(&mut binary_reader, header.type_count);
+        let type_position = section_position(0x24, (header.type_offset as i64) << 2)?;
+        let type_entries = Self::read_type_entries(&binary_reader, type_position, header.type_count)?;
 
         // Read field entries
-        let position = (header.field_offset << 2) + data_offset; debug_assert!(position > 0);
-        binary_reader.set_position(position as usize);
-        let field_entries = Self::read_field_entries(&mut binary_reader, header.field_count);
-
-        let hash_offset = (header.string_hash_offset << 2) + data_offset;
-        let offset_offset = (header.string_offsets_offset << 2) + data_offset;
-        let string_offset = header.string_offset + data_offset as i32;
-
-        if let Some(string_lookup) = Self::read_strings(&mut binary_reader, header.hash_count, hash_offset, offset_offset, string_offset) {
-            let value_offset = ((header.value_offset as i32) << 2) + data_offset as i32;
-            return Some(Self::create_rdbn(binary_reader, value_offset, string_offset, root_entries, type_entries, field_entries, string_lookup))
-        } else {
-            None
+        let field_position = section_position(0x28, (header.field_offset as i64) << 2)?;
+        let field_entries = Self::read_field_entries(&binary_reader, field_position, header.field_count)?;
+
+        let hash_offset = section_position(0x30, (header.string_hash_offset as i64) << 2)?;
+        let offset_offset = section_position(0x32, (header.string_offsets_offset as i64) << 2)?;
+        let string_offset = section_position(0x38, header.string_offset as i64)?;
+
+        let string_lookup = Self::read_strings(&mut binary_reader, header.hash_count, hash_offset, offset_offset, string_offset, decoding)?;
+
+        let layout = RdbnLayout {
+            type_position,
+            field_position,
+            root_position,
+            value_offset: section_position(0x36, (header.value_offset as i64) << 2)?,
+            string_offset,
+        };
+
+        // A data size that matches the file is computed again when writing, so that it follows the edits
+        let mut opaque_header = RdbnOpaqueHeader::from(&header);
+        if header.data_size as i64 == file.len() as i64 - data_offset {
+            opaque_header.data_size = None;
         }
+
+        Self::create_rdbn(binary_reader, opaque_header, layout, root_entries, type_entries, field_entries, string_lookup, decoding, dictionary)
     }
 
-    fn read_root_entries(binary_reader: &mut BinaryReader, root_count: i16) -> Vec<RdbnRootEntry> {
-        debug_assert!(root_count > 0);
-        let count = root_count as usize;
-        
+    fn read_root_entries(binary_reader: &BinaryReader, position: usize, root_count: i16) -> Result<Vec<RdbnRootEntry>, CfgBinError> {
+        let (count, mut entry_reader) = entry_window(binary_reader, position, root_count, "RDBN root entries")?;
+
         let mut result: Vec<RdbnRootEntry> = Vec::with_capacity(count);
 
         for _ in 0..count {
-            result.push(RdbnRootEntry::new(binary_reader));
-            binary_reader.skip(12); // The RdbnRootEntry is only 20 bytes long, but the entries are 0x20 = 32 aligned
+            result.push(RdbnRootEntry::new(&mut entry_reader)?);
+            entry_reader.skip(12)?; // The RdbnRootEntry is only 20 bytes long, but the entries are 0x20 = 32 aligned
         }
 
-        result
+        Ok(result)
     }
 
-    fn read_type_entries(binary_reader: &mut BinaryReader, type_count: i16) -> Vec<RdbnTypeEntry> {
-        debug_assert!(type_count > 0);
-        let count = type_count as usize;
+    fn read_type_entries(binary_reader: &BinaryReader, position: usize, type_count: i16) -> Result<Vec<RdbnTypeEntry>, CfgBinError> {
+        let (count, mut entry_reader) = entry_window(binary_reader, position, type_count, "RDBN type entries")?;
 
         let mut result = Vec::with_capacity(count);
         for _ in 0..count {
-            result.push(RdbnTypeEntry::new(binary_reader));
-            binary_reader.skip(20); // The RdbnTypeEntry is only 12 bytes long, but the entries are 0x20 = 32 aligned
+            result.push(RdbnTypeEntry::new(&mut entry_reader)?);
+            entry_reader.skip(20)?; // The RdbnTypeEntry is only 12 bytes long, but the entries are 0x20 = 32 aligned
         }
 
-        result
+        Ok(result)
     }
 
-    fn read_field_entries(binary_reader: &mut BinaryReader, field_count: i16) -> Vec<RdbnFieldEntry> {
-        debug_assert!(field_count > 0);
-        let count = field_count as usize;
+    fn read_field_entries(binary_reader: &BinaryReader, position: usize, field_count: i16) -> Result<Vec<RdbnFieldEntry>, CfgBinError> {
+        let (count, mut entry_reader) = entry_window(binary_reader, position, field_count, "RDBN field entries")?;
 
         let mut result = Vec::with_capacity(count);
         for _ in 0..count {
-            result.push(RdbnFieldEntry::new(binary_reader));
-            binary_reader.skip(12); // The RdbnFieldEntry is only 20 bytes long, but the entries are 0x20 = 32 aligned
+            result.push(RdbnFieldEntry::new(&mut entry_reader)?);
+            entry_reader.skip(12)?; // The RdbnFieldEntry is only 20 bytes long, but the entries are 0x20 = 32 aligned
         }
 
-        result
+        Ok(result)
     }
 
-    fn read_strings(binary_reader: &mut BinaryReader, hash_count: i16, hash_offset: i16, offset_offset: i16, string_offset: i32) -> Option<HashMap<u32, String>> {
-        debug_assert!(hash_count > 0);
+    fn read_strings(binary_reader: &mut BinaryReader, hash_count: i16, hash_offset: usize, offset_offset: usize, string_offset: usize, decoding: StringDecoding) -> Result<Vec<RdbnString>, CfgBinError> {
+        let count = usize::try_from(hash_count)
+            .map_err(|_| CfgBinError::InvalidOffset { offset: 0x34, section: "RDBN header", value: hash_count as i64 })?;
 
-        let count = hash_count as usize;
         let mut hashes = Vec::with_capacity(count);
         let mut offsets = Vec::with_capacity(count);
 
-        debug_assert!(hash_offset > 0);
-        binary_reader.set_position(hash_offset as usize);
-
+        let mut hash_reader = binary_reader.window(hash_offset, count * 4, "RDBN string hashes")?;
         for _ in 0..count {
-            hashes.push(binary_reader.read_u32());
+            hashes.push(hash_reader.read_u32()?);
         }
 
-        debug_assert!(offset_offset > 0);
-        binary_reader.set_position(offset_offset as usize);
-
+        let mut offset_reader = binary_reader.window(offset_offset, count * 4, "RDBN string offsets")?;
         for _ in 0..count {
-            offsets.push(binary_reader.read_i32());
+            offsets.push(offset_reader.read_i32()?);
         }
 
-        let mut result = HashMap::with_capacity(count);
+        let mut result = Vec::with_capacity(count);
 
         for i in 0..count {
-            if (string_offset + offsets[i]) as usize > binary_reader.file_size() {
-                return None
+            let position = string_offset as i64 + offsets[i] as i64;
+            if position < 0 || position as usize >= binary_reader.file_size() {
+                return Err(CfgBinError::StringOffsetOutOfRange { offset: offset_offset + i * 4, section: "RDBN string offsets", string_offset: offsets[i] as i64 })
             }
 
-            debug_assert!(string_offset + offsets[i] > 0);
-            binary_reader.set_position((string_offset + offsets[i]) as usize);
-            result.insert(hashes[i], Self::read_string(binary_reader));
+            binary_reader.set_position(position as usize)?;
+            let name = Self::read_string(binary_reader)?;
+            result.push(decoding.decode_name(hashes[i], name, position as usize, "RDBN strings")?);
         }
 
-        Some(result)
+        Ok(result)
     }
 
-    fn read_string(binary_reader: &mut BinaryReader) -> String {
-        let mut result = Vec::new();
+    /// Reads the bytes of a null-terminated string, without the terminator
+    fn read_string<'a>(binary_reader: &mut BinaryReader<'a>) -> Result<&'a [u8], CfgBinError> {
+        let section = "RDBN strings";
 
-        let mut byte = binary_reader.read_byte();
-        while byte != 0 {
-            result.push(byte);
-            byte = binary_reader.read_byte();
-        }
+        let length = binary_reader.remaining_bytes()
+            .iter()
+            .position(|byte| *byte == 0)
+            .ok_or(CfgBinError::Truncated { offset: binary_reader.file_position(), section, size: binary_reader.remaining_bytes().len() + 1 })?;
 
-        String::from_utf8(result).unwrap()
+        let result = binary_reader.read_bytes(length)?;
+        binary_reader.skip(1)?;
+
+        Ok(result)
     }
 
-    fn create_rdbn(mut binary_reader: BinaryReader, value_offset: i32, string_offset: i32, root_entries: Vec<RdbnRootEntry>, type_entries: Vec<RdbnTypeEntry>, field_entries: Vec<RdbnFieldEntry>, string_lookup: HashMap<u32, String>) -> Rdbn {
+    #[allow(clippy::too_many_arguments)]
+    fn create_rdbn(mut binary_reader: BinaryReader, header: RdbnOpaqueHeader, layout: RdbnLayout, root_entries: Vec<RdbnRootEntry>, type_entries: Vec<RdbnTypeEntry>, field_entries: Vec<RdbnFieldEntry>, string_lookup: Vec<RdbnString>, decoding: StringDecoding, dictionary: &HashDictionary) -> Result<Rdbn, CfgBinError> {
+        let mut names = HashMap::with_capacity(string_lookup.len());
+        for string in &string_lookup {
+            names.entry(string.hash).or_insert(string.name.as_str());
+        }
+
+        let mut dictionary_names = BTreeMap::new();
+        let mut lookup_name = |hash: u32, offset: usize, section: &'static str| {
+            if let Some(name) = names.get(&hash) {
+                return Ok(name.to_string())
+            }
+
+            let name = dictionary.get(hash).ok_or(CfgBinError::UnknownHash { offset, section, hash })?;
+            dictionary_names.insert(hash, name.to_string());
+            Ok(name.to_string())
+        };
+
+        let mut type_fields = Vec::with_capacity(type_entries.len());
         let mut type_declarations = Vec::with_capacity(type_entries.len());
-        for type_entry in &type_entries {
-            debug_assert!(type_entry.field_count > 0);
-            let mut field_declarations = Vec::with_capacity(type_entry.field_count as usize);
+        for (i, type_entry) in type_entries.iter().enumerate() {
+            let type_entry_offset = layout.type_position + i * ENTRY_ALIGNMENT;
+
+            let fields = usize::try_from(type_entry.field_index).ok()
+                .zip(usize::try_from(type_entry.field_count).ok())
+                .and_then(|(index, count)| field_entries.get(index..index + count))
+                .ok_or(CfgBinError::IndexOutOfRange {
+                    offset: type_entry_offset + 8,
+                    section: "RDBN type entries",
+                    index: type_entry.field_index as i64 + type_entry.field_count as i64,
+                    count: field_entries.len(),
+                })?;
 
-            for j in 0..type_entry.field_count as usize {
-                debug_assert!(type_entry.field_index >= 0);
-                let field_entry = &field_entries[type_entry.field_index as usize + j];
+            let mut field_declarations = Vec::with_capacity(fields.len());
+
+            for (j, field_entry) in fields.iter().enumerate() {
+                let field_entry_offset = layout.field_position + (type_entry.field_index as usize + j) * ENTRY_ALIGNMENT;
+                let section = "RDBN field entries";
 
                 field_declarations.push(RdbnFieldDeclaration {
-                    name: string_lookup[&field_entry.name_hash].clone(),
+                    name: lookup_name(field_entry.name_hash, field_entry_offset, section)?,
                     count: field_entry.value_count,
                     size: field_entry.value_size,
-                    field_type: RdbnFieldType::try_from(field_entry.r#type).unwrap(),
-                    field_type_category: RdbnFieldTypeCategory::try_from(field_entry.type_category).unwrap(),
+                    offset: field_entry.value_offset,
+                    field_type: field_entry.field_type(),
+                    field_type_category: RdbnFieldTypeCategory::try_from(field_entry.type_category)
+                        .map_err(|_| CfgBinError::UnknownFieldCategory { offset: field_entry_offset + 6, section, category: field_entry.type_category })?,
                 });
             }
 
             type_declarations.push(RdbnTypeDeclaration {
-                name: string_lookup[&type_entry.name_hash].clone(),
+                name: lookup_name(type_entry.name_hash, type_entry_offset, "RDBN type entries")?,
                 unk_hash: type_entry.unk1,
                 fields: field_declarations,
+                field_index: Some(type_entry.field_index as usize),
             });
+            type_fields.push(fields);
         }
 
-        let mut distinct_types = Vec::<RdbnTypeDeclaration>::new();
-        let mut lookup = HashMap::<RdbnTypeDeclaration, usize>::new();
-
-        for ty in &type_declarations {
-            if lookup.get(&ty).is_none() {
-                let idx = distinct_types.len();
-                distinct_types.push(ty.clone());
-                lookup.insert(ty.clone(), idx);
-            }
-        }
+        // The string values are the last section, and their offsets are relative to its start
+        let string_table = binary_reader.window(layout.string_offset, binary_reader.file_size() - layout.string_offset, "RDBN strings")?;
 
         let mut lists = Vec::with_capacity(root_entries.len());
 
-        for root_entry in &root_entries {
-            debug_assert!(root_entry.value_count > 0);
-            let mut list_values = Vec::with_capacity(root_entry.value_count as usize);
-
-            let root_value_offset = value_offset as i32 + root_entry.value_offset;
-
-            for j in 0..root_entry.value_count {
-                let type_entry = &type_entries[root_entry.type_index as usize];
-
-                list_values.push(Vec::with_capacity(type_entry.field_count as usize));
-
-                let type_value_offset = root_value_offset + j * root_entry.value_size;
-
-                for h in 0..type_entry.field_count {
-                    let field_entry = &field_entries[(type_entry.field_index + h) as usize];
-
-                    list_values[j as usize].push(Vec::with_capacity(field_entry.value_count as usize));
-
-                    binary_reader.set_position((type_value_offset + field_entry.value_offset) as usize);
-
-                    for _ in 0..field_entry.value_count {
-                        match field_entry.r#type {
-                            // Ability Data
-                            0..3 => list_values[j as usize][h as usize].push(
-                                RdbnValue::Bytes(binary_reader.read_bytes(field_entry.value_size as usize).to_vec())
-                            ),
-                            3 => list_values[j as usize][h as usize].push(
-                                RdbnValue::Bool(binary_reader.read_bool())
-                            ),
-                            4 => list_values[j as usize][h as usize].push(
-                                RdbnValue::Byte(binary_reader.read_byte())
-                            ),
-                            5 | 9 => list_values[j as usize][h as usize].push(
-                                RdbnValue::Short(binary_reader.read_i16())
-                            ),
-                            6 | 10 => list_values[j as usize][h as usize].push(
-                                RdbnValue::Int(binary_reader.read_i32())
-                            ),
-                            0xD => list_values[j as usize][h as usize].push(
-                                RdbnValue::Float(binary_reader.read_f32())
-                            ),
-                            0xF => list_values[j as usize][h as usize].push(
-                                RdbnValue::Uint(binary_reader.read_u32())
-                            ),
-                            0x12 | 0x13 => list_values[j as usize][h as usize].push(
-                                RdbnValue::Float4([binary_reader.read_f32(), binary_reader.read_f32(), binary_reader.read_f32(), binary_reader.read_f32()])
-                            ),
-                            0x14 => {
-                                let condition_value = binary_reader.read_u32();
-
-                                if string_offset as usize + condition_value as usize >= binary_reader.file_size() {
-                                    list_values[j as usize][h as usize].push(
-                                        RdbnValue::Uint(condition_value)
-                                    );
+        for (i, root_entry) in root_entries.iter().enumerate() {
+            let root_entry_offset = layout.root_position + i * ENTRY_ALIGNMENT;
+            let section = "RDBN root entries";
+
+            let type_index = usize::try_from(root_entry.type_index).ok()
+                .filter(|index| *index < type_entries.len())
+                .ok_or(CfgBinError::IndexOutOfRange { offset: root_entry_offset, section, index: root_entry.type_index as i64, count: type_entries.len() })?;
+            let value_count = usize::try_from(root_entry.value_count)
+                .map_err(|_| CfgBinError::InvalidOffset { offset: root_entry_offset + 12, section, value: root_entry.value_count as i64 })?;
+
+            let root_value_offset = layout.value_offset as i64 + root_entry.value_offset as i64;
+
+            // Every row takes at least one byte, which keeps a corrupted count from allocating more than the file could hold
+            let list_position = usize::try_from(root_value_offset)
+                .map_err(|_| CfgBinError::InvalidOffset { offset: root_entry_offset + 4, section, value: root_value_offset })?;
+            binary_reader.ensure_available(list_position, value_count * root_entry.value_size.max(1) as usize, "RDBN values")?;
+
+            let mut list_values = Vec::with_capacity(value_count);
+
+            for j in 0..value_count {
+                let fields = type_fields[type_index];
+
+                let mut row = Vec::with_capacity(fields.len());
+
+                let type_value_offset = root_value_offset + j as i64 * root_entry.value_size as i64;
+
+                for field_entry in fields {
+                    let section = "RDBN values";
+
+                    let position = type_value_offset + field_entry.value_offset as i64;
+                    let position = usize::try_from(position)
+                        .map_err(|_| CfgBinError::InvalidOffset { offset: root_entry_offset + 4, section, value: position })?;
+
+                    // The values are read with the size of their type, except for the composite and unknown ones
+                    let field_type = field_entry.field_type();
+                    let count = usize::try_from(field_entry.value_count).unwrap_or(0);
+                    let size = field_type.size().unwrap_or(field_entry.value_size);
+                    binary_reader.ensure_available(position, count * size.max(1) as usize, section)?;
+
+                    binary_reader.set_section(section);
+                    binary_reader.set_position(position)?;
+
+                    let mut values = Vec::with_capacity(count);
+
+                    for _ in 0..count {
+                        let value = match field_type {
+                            // Composite values and values of unknown types are kept as they are
+                            RdbnFieldType::AbilityData | RdbnFieldType::EnhanceData | RdbnFieldType::StatusRate | RdbnFieldType::Unknown(_) =>
+                                RdbnValue::Bytes(binary_reader.read_bytes(field_entry.value_size as usize)?.to_vec()),
+                            RdbnFieldType::Bool => RdbnValue::Bool(binary_reader.read_bool()?),
+                            RdbnFieldType::Byte => RdbnValue::Byte(binary_reader.read_byte()?),
+                            RdbnFieldType::Short | RdbnFieldType::ActType => RdbnValue::Short(binary_reader.read_i16()?),
+                            RdbnFieldType::Int | RdbnFieldType::Flag => RdbnValue::Int(binary_reader.read_i32()?),
+                            RdbnFieldType::Long => RdbnValue::Long(binary_reader.read_i64()?),
+                            RdbnFieldType::UInt => RdbnValue::Uint(binary_reader.read_u32()?),
+                            RdbnFieldType::UShort => RdbnValue::UShort(binary_reader.read_u16()?),
+                            RdbnFieldType::Float => RdbnValue::Float(binary_reader.read_f32()?),
+                            RdbnFieldType::Double => RdbnValue::Double(binary_reader.read_f64()?),
+                            RdbnFieldType::Float2 => RdbnValue::Float2([binary_reader.read_f32()?, binary_reader.read_f32()?]),
+                            RdbnFieldType::Float3 => RdbnValue::Float3([binary_reader.read_f32()?, binary_reader.read_f32()?, binary_reader.read_f32()?]),
+                            RdbnFieldType::Int2 => RdbnValue::Int2([binary_reader.read_i32()?, binary_reader.read_i32()?]),
+                            RdbnFieldType::Hash => RdbnValue::Hash(binary_reader.read_u32()?),
+                            RdbnFieldType::RateMatrix | RdbnFieldType::Position => RdbnValue::Float4([binary_reader.read_f32()?, binary_reader.read_f32()?, binary_reader.read_f32()?, binary_reader.read_f32()?]),
+                            RdbnFieldType::String => {
+                                let value_position = binary_reader.file_position();
+                                let string_offset = binary_reader.read_u32()?;
+
+                                if string_offset == NULL_STRING_OFFSET {
+                                    RdbnValue::NullString
+                                } else if string_offset as usize >= string_table.file_size() {
+                                    // Some files store other values in string fields, which are kept as they are
+                                    if decoding == StringDecoding::Strict {
+                                        return Err(CfgBinError::StringOffsetOutOfRange { offset: value_position, section, string_offset: string_offset as i64 })
+                                    }
+
+                                    RdbnValue::StringOffset(string_offset)
                                 } else {
-                                    binary_reader.set_position(string_offset as usize + condition_value as usize);
-                                    list_values[j as usize][h as usize].push(
-                                        RdbnValue::String(Self::read_string(&mut binary_reader))
-                                    );
+                                    let mut string_reader = string_table.clone();
+                                    string_reader.set_position(string_offset as usize)?;
+                                    let string = Self::read_string(&mut string_reader)?;
+
+                                    decoding.decode_value(string, layout.string_offset + string_offset as usize, "RDBN strings")?
                                 }
                             }
-                            0x15 => list_values[j as usize][h as usize].push(
-                                RdbnValue::Short2([binary_reader.read_i16(), binary_reader.read_i16()])
-                            ),
-                            _ => panic!("Invalid field type {:X}", field_entry.r#type)
-                        }
+                            RdbnFieldType::DataTuple => RdbnValue::Short2([binary_reader.read_i16()?, binary_reader.read_i16()?]),
+                        };
+
+                        values.push(value);
                     }
+
+                    row.push(values);
                 }
+
+                list_values.push(row);
             }
 
             lists.push(RdbnListEntry {
-                name: string_lookup[&root_entry.name_hash].clone(),
-                type_index: lookup[&type_declarations[root_entry.type_index as usize]],
+                name: lookup_name(root_entry.name_hash, root_entry_offset, section)?,
+                type_index,
+                value_size: root_entry.value_size,
+                unk1: root_entry.unk1,
                 values: list_values,
             });
         }
 
-        return Rdbn {
-            types: distinct_types,
+        Ok(Rdbn {
+            header,
+            types: type_declarations,
             lists,
+            string_lookup,
+            dictionary_names,
+        })
+    }
+
+    /// The string of the string table with the hash `hash`
+    pub fn string(&self, hash: u32) -> Option<&str> {
+        self.string_lookup.iter().find(|string| string.hash == hash).map(|string| string.name.as_str())
+    }
+
+    /// The types of the type table without the repeated declarations, which some files have.
+    /// Types are repeated when they have the same name, `unk_hash` and fields, wherever the fields are in the field table.
+    pub fn distinct_types(&self) -> Vec<&RdbnTypeDeclaration> {
+        let mut result: Vec<&RdbnTypeDeclaration> = Vec::new();
+
+        for ty in &self.types {
+            if !result.iter().any(|known| known.name == ty.name && known.unk_hash == ty.unk_hash && known.fields == ty.fields) {
+                result.push(ty);
+            }
+        }
+
+        result
+    }
+
+    pub fn write<W: Write>(&self, output: &mut W) -> io::Result<()> {
+        output.write_all(&self.to_bytes()?)
+    }
+
+    /// Encodes the file. The string table is written back with its hashes, followed by the names that are neither in it
+    /// nor in the dictionary names, hashed like the names of the string table.
+    pub fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        let mut strings = StringPool::new();
+        let mut hashes = StringTableWriter::new(&self.string_lookup, &self.dictionary_names, &mut strings);
+
+        let (fields, field_indices) = place_fields(&self.types)?;
+        let mut type_entries = Vec::with_capacity(self.types.len());
+
+        for (type_declaration, field_index) in self.types.iter().zip(field_indices) {
+            type_entries.push(RdbnTypeEntry {
+                name_hash: hashes.hash(&type_declaration.name, &mut strings),
+                unk1: type_declaration.unk_hash,
+                field_index: field_index as i16,
+                field_count: type_declaration.fields.len() as i16,
+            });
+        }
+
+        let field_entries: Vec<RdbnFieldEntry> = fields.into_iter().map(|field| RdbnFieldEntry {
+            name_hash: hashes.hash(&field.name, &mut strings),
+            r#type: field.field_type.into(),
+            type_category: field.field_type_category as i16,
+            value_size: field.size,
+            value_offset: field.offset,
+            value_count: field.count,
+        }).collect();
+
+        let list_hashes: Vec<u32> = self.lists.iter().map(|list| hashes.hash(&list.name, &mut strings)).collect();
+
+        // The values are written in their own buffer, since the string values have to be added to the string pool first
+        let mut value_writer = BinaryWriter::new();
+        let mut root_entries = Vec::with_capacity(self.lists.len());
+
+        for (list, name_hash) in self.lists.iter().zip(list_hashes) {
+            value_writer.seek_alignment(4);
+            let list_offset = value_writer.position();
+
+            let type_declaration = self.types.get(list.type_index).ok_or_else(|| invalid_data(
+                format!("List {} references the missing type {}", list.name, list.type_index)
+            ))?;
+            check_field_layout(list, type_declaration)?;
+
+            for (j, row) in list.values.iter().enumerate() {
+                if row.len() != type_declaration.fields.len() {
+                    return Err(invalid_data(format!("Row {j} of list {} has {} fields instead of {}", list.name, row.len(), type_declaration.fields.len())))
+                }
+
+                let row_offset = list_offset + j * list.value_size as usize;
+
+                for (field, values) in type_declaration.fields.iter().zip(row) {
+                    if values.len() != field.count as usize {
+                        return Err(invalid_data(format!("Field {} of list {} has {} values instead of {}", field.name, list.name, values.len(), field.count)))
+                    }
+
+                    value_writer.set_position(row_offset + field.offset as usize);
+
+                    for value in values {
+                        Self::write_value(&mut value_writer, field, value, &mut strings)?;
+                    }
+                }
+            }
+
+            value_writer.set_position(list_offset + list.values.len() * list.value_size as usize);
+
+            root_entries.push(RdbnRootEntry {
+                type_index: list.type_index as i16,
+                unk1: list.unk1,
+                value_offset: list_offset as i32,
+                value_size: list.value_size,
+                value_count: list.values.len() as i32,
+                name_hash,
+            });
         }
+
+        check_string_offsets(&self.lists, strings.data().len().next_multiple_of(ENTRY_ALIGNMENT))?;
+
+        let mut binary_writer = BinaryWriter::new();
+        binary_writer.set_position(DATA_OFFSET);
+
+        let type_offset = binary_writer.position();
+        for type_entry in &type_entries {
+            type_entry.write(&mut binary_writer);
+            binary_writer.seek_alignment(ENTRY_ALIGNMENT); // The RdbnTypeEntry is only 12 bytes long, but the entries are 0x20 = 32 aligned
+        }
+
+        let field_offset = binary_writer.position();
+        for field_entry in &field_entries {
+            field_entry.write(&mut binary_writer);
+            binary_writer.seek_alignment(ENTRY_ALIGNMENT); // The RdbnFieldEntry is only 20 bytes long, but the entries are 0x20 = 32 aligned
+        }
+
+        let root_offset = binary_writer.position();
+        for root_entry in &root_entries {
+            root_entry.write(&mut binary_writer);
+            binary_writer.seek_alignment(ENTRY_ALIGNMENT); // The RdbnRootEntry is only 20 bytes long, but the entries are 0x20 = 32 aligned
+        }
+
+        let string_hash_offset = binary_writer.position();
+        for (hash, _) in hashes.entries() {
+            binary_writer.write_u32(*hash);
+        }
+        binary_writer.seek_alignment(ENTRY_ALIGNMENT);
+
+        let string_offsets_offset = binary_writer.position();
+        for (_, offset) in hashes.entries() {
+            binary_writer.write_i32(*offset as i32);
+        }
+        binary_writer.seek_alignment(ENTRY_ALIGNMENT);
+
+        let value_offset = binary_writer.position();
+        binary_writer.write_bytes(&value_writer.into_inner());
+        binary_writer.seek_alignment(ENTRY_ALIGNMENT);
+
+        let string_offset = binary_writer.position();
+        binary_writer.write_bytes(strings.data());
+        binary_writer.seek_alignment(ENTRY_ALIGNMENT);
+
+        let relative = |position: usize| -> io::Result<i16> {
+            i16::try_from((position - DATA_OFFSET) >> 2).map_err(|_| invalid_data(
+                format!("Section offset {position:#X} does not fit in the RDBN header")
+            ))
+        };
+
+        let header = RdbnHeader {
+            magic: RDBN_HEADER,
+            header_size: self.header.header_size,
+            version: self.header.version,
+            data_offset: (DATA_OFFSET >> 2) as i16,
+            data_size: self.header.data_size.unwrap_or((binary_writer.file_size() - DATA_OFFSET) as i32),
+            reserved: self.header.reserved,
+
+            type_offset: relative(type_offset)?,
+            type_count: type_entries.len() as i16,
+            field_offset: relative(field_offset)?,
+            field_count: field_entries.len() as i16,
+            root_offset: relative(root_offset)?,
+            root_count: root_entries.len() as i16,
+            string_hash_offset: relative(string_hash_offset)?,
+            string_offsets_offset: relative(string_offsets_offset)?,
+            hash_count: hashes.entries().len() as i16,
+            value_offset: relative(value_offset)?,
+            string_offset: (string_offset - DATA_OFFSET) as i32,
+        };
+
+        binary_writer.set_position(0);
+        header.write(&mut binary_writer);
+
+        Ok(binary_writer.into_inner())
     }
+
+    fn write_value(binary_writer: &mut BinaryWriter, field: &RdbnFieldDeclaration, value: &RdbnValue, strings: &mut StringPool) -> io::Result<()> {
+        match (field.field_type, value) {
+            (RdbnFieldType::AbilityData | RdbnFieldType::EnhanceData | RdbnFieldType::StatusRate | RdbnFieldType::Unknown(_), RdbnValue::Bytes(v)) => {
+                if v.len() != field.size as usize {
+                    return Err(invalid_data(format!("Field {} expects {} bytes, got {}", field.name, field.size, v.len())))
+                }
+                binary_writer.write_bytes(v)
+            },
+            (RdbnFieldType::Bool, RdbnValue::Bool(v)) => binary_writer.write_bool(*v),
+            (RdbnFieldType::Byte, RdbnValue::Byte(v)) => binary_writer.write_byte(*v),
+            (RdbnFieldType::Short | RdbnFieldType::ActType, RdbnValue::Short(v)) => binary_writer.write_i16(*v),
+            (RdbnFieldType::Int | RdbnFieldType::Flag, RdbnValue::Int(v)) => binary_writer.write_i32(*v),
+            (RdbnFieldType::Long, RdbnValue::Long(v)) => binary_writer.write_i64(*v),
+            (RdbnFieldType::UInt, RdbnValue::Uint(v)) => binary_writer.write_u32(*v),
+            (RdbnFieldType::UShort, RdbnValue::UShort(v)) => binary_writer.write_u16(*v),
+            (RdbnFieldType::Float, RdbnValue::Float(v)) => binary_writer.write_f32(*v),
+            (RdbnFieldType::Double, RdbnValue::Double(v)) => binary_writer.write_f64(*v),
+            (RdbnFieldType::Float2, RdbnValue::Float2(v)) => v.iter().for_each(|f| binary_writer.write_f32(*f)),
+            (RdbnFieldType::Float3, RdbnValue::Float3(v)) => v.iter().for_each(|f| binary_writer.write_f32(*f)),
+            (RdbnFieldType::Int2, RdbnValue::Int2(v)) => v.iter().for_each(|i| binary_writer.write_i32(*i)),
+            (RdbnFieldType::Hash, RdbnValue::Uint(v) | RdbnValue::Hash(v)) => binary_writer.write_u32(*v),
+            (RdbnFieldType::RateMatrix | RdbnFieldType::Position, RdbnValue::Float4(v)) => {
+                for f in v {
+                    binary_writer.write_f32(*f);
+                }
+            },
+            (RdbnFieldType::String, RdbnValue::String(v)) => binary_writer.write_u32(strings.add(v.as_bytes())),
+            (RdbnFieldType::String, RdbnValue::Bytes(v)) => binary_writer.write_u32(strings.add(v)),
+            (RdbnFieldType::String, RdbnValue::NullString) => binary_writer.write_u32(NULL_STRING_OFFSET),
+            (RdbnFieldType::String, RdbnValue::StringOffset(v)) => binary_writer.write_u32(*v),
+            (RdbnFieldType::DataTuple, RdbnValue::Short2([a, b])) => {
+                binary_writer.write_i16(*a);
+                binary_writer.write_i16(*b);
+            },
+            (field_type, value) => return Err(invalid_data(format!("Field {} of type {:?} cannot hold {:?}", field.name, field_type, value))),
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks that the offsets kept in string fields still point past the string table, which the strings added by the writer
+/// can make longer. Otherwise they would be read back as strings.
+fn check_string_offsets(lists: &[RdbnListEntry], string_table_size: usize) -> io::Result<()> {
+    for list in lists {
+        let offsets = list.values.iter().flatten().flatten().filter_map(|value| match value {
+            RdbnValue::StringOffset(offset) => Some(*offset),
+            _ => None,
+        });
+
+        if let Some(offset) = offsets.filter(|offset| (*offset as usize) < string_table_size).min() {
+            return Err(invalid_data(format!(
+                "List {} holds the string offset {offset:#X}, which points into the string table of {string_table_size:#X} bytes", list.name
+            )))
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that the values of every field stay within the rows of the list, so that writing them
+/// doesn't overwrite the next row or the next list
+fn check_field_layout(list: &RdbnListEntry, type_declaration: &RdbnTypeDeclaration) -> io::Result<()> {
+    if list.value_size < 0 {
+        return Err(invalid_data(format!("List {} has the negative row size {}", list.name, list.value_size)))
+    }
+
+    for field in &type_declaration.fields {
+        // The values are written with the size of their type, except for the composite and unknown ones
+        let size = field.field_type.size().unwrap_or(field.size) as i64 * field.count as i64;
+
+        if field.offset < 0 || size < 0 || field.offset as i64 + size > list.value_size as i64 {
+            return Err(invalid_data(format!(
+                "Field {} of list {} takes {size} bytes at offset {}, outside of the rows of {} bytes",
+                field.name, list.name, field.offset, list.value_size
+            )))
+        }
+    }
+
+    Ok(())
+}
+
+/// Builds the field table and returns the index of the first field of every type.
+/// The types keep their field index when their fields can be placed there, which lets identical types share their fields
+/// like in the file they were read from. The other types get their fields appended to the table.
+fn place_fields(types: &[RdbnTypeDeclaration]) -> io::Result<(Vec<&RdbnFieldDeclaration>, Vec<usize>)> {
+    let mut slots: Vec<Option<&RdbnFieldDeclaration>> = Vec::new();
+    let mut indices = Vec::with_capacity(types.len());
+
+    for type_declaration in types {
+        let fields = &type_declaration.fields;
+
+        let fits = |start: usize| fields.iter().enumerate().all(|(i, field)| {
+            slots.get(start + i).is_none_or(|slot| slot.is_none_or(|placed| placed == field))
+        });
+
+        let start = match type_declaration.field_index {
+            Some(start) if start.saturating_add(fields.len()) > MAX_ENTRY_COUNT => return Err(invalid_data(
+                format!("Type {} has the field index {start}, but a file holds at most {MAX_ENTRY_COUNT} fields", type_declaration.name)
+            )),
+            Some(start) if fits(start) => start,
+            _ => slots.len(),
+        };
+
+        if slots.len() < start + fields.len() {
+            slots.resize(start + fields.len(), None);
+        }
+        for (i, field) in fields.iter().enumerate() {
+            slots[start + i] = Some(field);
+        }
+
+        indices.push(start);
+    }
+
+    // A field index past the end of the other fields leaves a gap, in which case every type gets its own fields
+    match slots.into_iter().collect::<Option<Vec<_>>>() {
+        Some(fields) => Ok((fields, indices)),
+        None => {
+            let mut fields = Vec::new();
+            let indices = types.iter().map(|type_declaration| {
+                fields.extend(&type_declaration.fields);
+                fields.len() - type_declaration.fields.len()
+            }).collect();
+
+            Ok((fields, indices))
+        }
+    }
+}
+
+/// Returns the number of entries of a section and a reader over them
+fn entry_window<'a>(binary_reader: &BinaryReader<'a>, position: usize, count: i16, section: &'static str) -> Result<(usize, BinaryReader<'a>), CfgBinError> {
+    let count = usize::try_from(count)
+        .map_err(|_| CfgBinError::InvalidOffset { offset: position, section, value: count as i64 })?;
+
+    Ok((count, binary_reader.window(position, count * ENTRY_ALIGNMENT, section)?))
 }
\ No newline at end of file
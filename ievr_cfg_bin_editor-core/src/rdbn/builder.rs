@@ -0,0 +1,137 @@
+use std::io;
+
+use crate::common::invalid_data;
+
+use super::{editing::check_field, Rdbn, RdbnFieldDeclaration, RdbnListEntry, RdbnTypeDeclaration, RdbnValue};
+
+/// Builds an RDBN file, or extends one that was read, with new types and lists.
+///
+/// The fields of the new types are laid out with [`RdbnTypeDeclaration::compute_layout`]. The writer adds the names
+/// of the types, fields and lists that are not in the string table yet, hashed like the names already in there.
+#[derive(Debug, Clone)]
+pub struct RdbnBuilder {
+    rdbn: Rdbn,
+}
+
+impl RdbnBuilder {
+    /// Starts an empty file
+    pub fn new() -> RdbnBuilder {
+        RdbnBuilder {
+            rdbn: Rdbn {
+                header: Default::default(),
+                types: Vec::new(),
+                lists: Vec::new(),
+                string_lookup: Default::default(),
+                dictionary_names: Default::default(),
+            },
+        }
+    }
+
+    /// Adds a type and returns its index. The offsets of the fields are computed, so only their name, type,
+    /// count, and the size of the composite and unknown types matter, see [`RdbnFieldDeclaration::new`].
+    /// `unk_hash` is written in the type entry as is.
+    pub fn add_type(&mut self, name: &str, unk_hash: u32, fields: Vec<RdbnFieldDeclaration>) -> io::Result<usize> {
+        if name.is_empty() {
+            return Err(invalid_data("A type needs a name".to_string()))
+        }
+
+        for (i, field) in fields.iter().enumerate() {
+            check_field(field)?;
+
+            if fields[..i].iter().any(|known| known.name == field.name) {
+                return Err(invalid_data(format!("Type {name} has two fields named {}", field.name)))
+            }
+        }
+
+        let mut type_declaration = RdbnTypeDeclaration { name: name.to_string(), unk_hash, fields, field_index: None };
+        type_declaration.compute_layout();
+
+        self.rdbn.types.push(type_declaration);
+        Ok(self.rdbn.types.len() - 1)
+    }
+
+    /// Adds a list of rows using the type at `type_index`, and returns its index.
+    /// Every row must hold the values of every field of the type, as many as the field count.
+    pub fn add_list(&mut self, name: &str, type_index: usize, rows: Vec<Vec<Vec<RdbnValue>>>) -> io::Result<usize> {
+        if name.is_empty() {
+            return Err(invalid_data("A list needs a name".to_string()))
+        }
+        if self.rdbn.lists.iter().any(|list| list.name == name) {
+            return Err(invalid_data(format!("There already is a list named {name}")))
+        }
+
+        let type_declaration = self.rdbn.types.get(type_index).ok_or_else(|| invalid_data(
+            format!("Type {type_index} is out of range, there are only {} types", self.rdbn.types.len())
+        ))?;
+
+        for (j, row) in rows.iter().enumerate() {
+            check_row(name, j, type_declaration, row)?;
+        }
+
+        // A type that was read keeps the row size of its lists, which can be larger than its fields
+        let value_size = self.rdbn.lists.iter()
+            .find(|list| list.type_index == type_index)
+            .map_or_else(|| type_declaration.row_size(), |list| list.value_size);
+
+        self.rdbn.lists.push(RdbnListEntry {
+            name: name.to_string(),
+            type_index,
+            value_size,
+            unk1: 0,
+            values: rows,
+        });
+        Ok(self.rdbn.lists.len() - 1)
+    }
+
+    /// Adds a row at the end of the list at `list_index`
+    pub fn push_row(&mut self, list_index: usize, row: Vec<Vec<RdbnValue>>) -> io::Result<()> {
+        let list_count = self.rdbn.lists.len();
+        let list = self.rdbn.lists.get_mut(list_index).ok_or_else(|| invalid_data(
+            format!("List {list_index} is out of range, there are only {list_count} lists")
+        ))?;
+
+        let type_declaration = self.rdbn.types.get(list.type_index).ok_or_else(|| invalid_data(
+            format!("List {} references the missing type {}", list.name, list.type_index)
+        ))?;
+        check_row(&list.name, list.values.len(), type_declaration, &row)?;
+
+        list.values.push(row);
+        Ok(())
+    }
+
+    pub fn build(self) -> Rdbn {
+        self.rdbn
+    }
+}
+
+impl Default for RdbnBuilder {
+    fn default() -> Self {
+        RdbnBuilder::new()
+    }
+}
+
+/// Extends a file that was read. Its types and lists are kept as they are.
+impl From<Rdbn> for RdbnBuilder {
+    fn from(rdbn: Rdbn) -> Self {
+        RdbnBuilder { rdbn }
+    }
+}
+
+/// Checks that the row `j` of a list has the fields of its type, each with as many values as its count
+fn check_row(list: &str, j: usize, type_declaration: &RdbnTypeDeclaration, row: &[Vec<RdbnValue>]) -> io::Result<()> {
+    if row.len() != type_declaration.fields.len() {
+        return Err(invalid_data(format!("Row {j} of list {list} has {} fields instead of {}", row.len(), type_declaration.fields.len())))
+    }
+
+    for (field, values) in type_declaration.fields.iter().zip(row) {
+        if values.len() != field.count as usize {
+            return Err(invalid_data(format!("Field {} of list {list} has {} values instead of {}", field.name, values.len(), field.count)))
+        }
+
+        if let Some(value) = values.iter().find(|value| !field.accepts(value)) {
+            return Err(invalid_data(format!("Field {} of type {:?} cannot hold {:?}", field.name, field.field_type, value)))
+        }
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -1,20 +1,148 @@
+use serde::{Deserialize, Serialize};
+
 use super::{
     field_type::RdbnFieldType, 
-    field_type_category::RdbnFieldTypeCategory
+    field_type_category::RdbnFieldTypeCategory,
+    list_entry::RdbnValue,
 };
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// A field of an RDBN type
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct RdbnFieldDeclaration {
     pub name: String,
+    /// How many values the field holds
     pub count: i32,
+    /// The size of one value in bytes
     pub size: i32,
+    /// Where the field starts in the row, in bytes
+    pub offset: i32,
     pub field_type: RdbnFieldType,
     pub field_type_category: RdbnFieldTypeCategory,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+impl RdbnFieldDeclaration {
+    /// A field with the size and category of its type, to be placed by [`RdbnTypeDeclaration::compute_layout`].
+    /// The composite and unknown types have no size of their own, so it has to be set on the field.
+    pub fn new(name: &str, field_type: RdbnFieldType, count: i32) -> RdbnFieldDeclaration {
+        RdbnFieldDeclaration {
+            name: name.to_string(),
+            count,
+            size: field_type.size().unwrap_or(0),
+            offset: 0,
+            field_type,
+            field_type_category: field_type.into(),
+        }
+    }
+
+    /// The value that new rows hold in this field: `0`, `false`, an empty string, or zeroed bytes of the field size
+    pub fn default_value(&self) -> RdbnValue {
+        match self.field_type {
+            RdbnFieldType::AbilityData | RdbnFieldType::EnhanceData | RdbnFieldType::StatusRate | RdbnFieldType::Unknown(_) =>
+                RdbnValue::Bytes(vec![0; self.size.max(0) as usize]),
+            RdbnFieldType::Bool => RdbnValue::Bool(false),
+            RdbnFieldType::Byte => RdbnValue::Byte(0),
+            RdbnFieldType::Short | RdbnFieldType::ActType => RdbnValue::Short(0),
+            RdbnFieldType::Int | RdbnFieldType::Flag => RdbnValue::Int(0),
+            RdbnFieldType::Long => RdbnValue::Long(0),
+            RdbnFieldType::UInt => RdbnValue::Uint(0),
+            RdbnFieldType::UShort => RdbnValue::UShort(0),
+            RdbnFieldType::Float => RdbnValue::Float(0.0),
+            RdbnFieldType::Double => RdbnValue::Double(0.0),
+            RdbnFieldType::Float2 => RdbnValue::Float2([0.0; 2]),
+            RdbnFieldType::Float3 => RdbnValue::Float3([0.0; 3]),
+            RdbnFieldType::Int2 => RdbnValue::Int2([0; 2]),
+            RdbnFieldType::Hash => RdbnValue::Hash(0),
+            RdbnFieldType::RateMatrix | RdbnFieldType::Position => RdbnValue::Float4([0.0; 4]),
+            RdbnFieldType::String => RdbnValue::String(String::new()),
+            RdbnFieldType::DataTuple => RdbnValue::Short2([0; 2]),
+        }
+    }
+
+    /// Whether the field can hold the value
+    pub fn accepts(&self, value: &RdbnValue) -> bool {
+        match (self.field_type, value) {
+            (RdbnFieldType::AbilityData | RdbnFieldType::EnhanceData | RdbnFieldType::StatusRate | RdbnFieldType::Unknown(_), RdbnValue::Bytes(v)) =>
+                v.len() == self.size as usize,
+            (RdbnFieldType::Bool, RdbnValue::Bool(_))
+                | (RdbnFieldType::Byte, RdbnValue::Byte(_))
+                | (RdbnFieldType::Short | RdbnFieldType::ActType, RdbnValue::Short(_))
+                | (RdbnFieldType::Int | RdbnFieldType::Flag, RdbnValue::Int(_))
+                | (RdbnFieldType::Long, RdbnValue::Long(_))
+                | (RdbnFieldType::UInt, RdbnValue::Uint(_))
+                | (RdbnFieldType::UShort, RdbnValue::UShort(_))
+                | (RdbnFieldType::Float, RdbnValue::Float(_))
+                | (RdbnFieldType::Double, RdbnValue::Double(_))
+                | (RdbnFieldType::Float2, RdbnValue::Float2(_))
+                | (RdbnFieldType::Float3, RdbnValue::Float3(_))
+                | (RdbnFieldType::Int2, RdbnValue::Int2(_))
+                | (RdbnFieldType::Hash, RdbnValue::Uint(_) | RdbnValue::Hash(_))
+                | (RdbnFieldType::RateMatrix | RdbnFieldType::Position, RdbnValue::Float4(_))
+                | (RdbnFieldType::String, RdbnValue::String(_) | RdbnValue::Bytes(_) | RdbnValue::NullString | RdbnValue::StringOffset(_))
+                | (RdbnFieldType::DataTuple, RdbnValue::Short2(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+/// An RDBN type, which describes the layout of the rows of the lists that use it
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct RdbnTypeDeclaration {
     pub name: String,
-    pub(crate) unk_hash: u32,
-    pub(crate) fields: Vec<RdbnFieldDeclaration>
+    /// Unknown hash of the type entry, kept to write the file back unchanged
+    pub unk_hash: u32,
+    pub fields: Vec<RdbnFieldDeclaration>,
+    /// Where the fields start in the field table of the file the type was read from.
+    /// The writer keeps it when it can, `None` places the fields after the ones of the previous types.
+    pub field_index: Option<usize>,
+}
+
+impl RdbnTypeDeclaration {
+    /// Places the fields one after the other, each aligned on its value size (at most 4 bytes),
+    /// and returns the resulting row size.
+    pub fn compute_layout(&mut self) -> i32 {
+        let mut offset = 0;
+        for field in &mut self.fields {
+            let alignment = field.size.clamp(1, 4);
+            offset = (offset + alignment - 1) / alignment * alignment;
+
+            field.offset = offset;
+            offset += field.size * field.count;
+        }
+
+        (offset + 3) / 4 * 4
+    }
+
+    /// The size of a row with the fields as they are currently laid out, aligned on 4 bytes
+    pub fn row_size(&self) -> i32 {
+        let end = self.fields.iter().map(|field| field.offset + field.size * field.count).max().unwrap_or(0);
+        (end + 3) / 4 * 4
+    }
+
+    /// Whether the fields, as they are currently laid out, have the size of their type,
+    /// don't overlap and stay within rows of `size` bytes
+    pub(crate) fn fits_in(&self, size: i32) -> bool {
+        let mut ranges = Vec::with_capacity(self.fields.len());
+
+        for field in &self.fields {
+            if field.field_type.size().is_some_and(|type_size| type_size != field.size) {
+                return false
+            }
+            ranges.push((field.offset, field.offset + field.size * field.count));
+        }
+
+        ranges.sort_unstable();
+
+        ranges.first().is_none_or(|(start, _)| *start >= 0)
+            && ranges.last().is_none_or(|(_, end)| *end <= size)
+            && ranges.windows(2).all(|pair| pair[0].1 <= pair[1].0)
+    }
+
+    /// Whether both types have the same name and fields, regardless of how the fields are laid out
+    pub(crate) fn has_same_fields(&self, other: &RdbnTypeDeclaration) -> bool {
+        self.name == other.name
+            && self.fields.len() == other.fields.len()
+            && self.fields.iter().zip(&other.fields).all(|(a, b)| {
+                a.name == b.name && a.field_type == b.field_type && a.count == b.count
+            })
+    }
 }
\ No newline at end of file
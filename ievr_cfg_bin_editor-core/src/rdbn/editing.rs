@@ -0,0 +1,197 @@
+use std::io;
+
+use crate::common::invalid_data;
+
+use super::{Rdbn, RdbnFieldDeclaration, RdbnFieldType, RdbnValue};
+
+// Every change to the fields of a type lays them out again with `compute_layout`,
+// and updates the row size and the rows of every list that uses the type
+impl Rdbn {
+    /// Inserts a field at `position` in the type at `type_index`.
+    /// The rows of the lists that use the type get [`default_value`](RdbnFieldDeclaration::default_value) for it.
+    pub fn add_field(&mut self, type_index: usize, position: usize, field: RdbnFieldDeclaration) -> io::Result<()> {
+        self.check_rows(type_index)?;
+        let type_declaration = &self.types[type_index];
+
+        if position > type_declaration.fields.len() {
+            return Err(invalid_data(format!("Cannot insert a field at {position}, type {} has {} fields", type_declaration.name, type_declaration.fields.len())))
+        }
+        if type_declaration.fields.iter().any(|known| known.name == field.name) {
+            return Err(invalid_data(format!("Type {} already has a field named {}", type_declaration.name, field.name)))
+        }
+        check_field(&field)?;
+
+        let values = vec![field.default_value(); field.count as usize];
+        self.types[type_index].fields.insert(position, field);
+
+        self.update_rows(type_index, |row| row.insert(position, values.clone()));
+        Ok(())
+    }
+
+    /// Removes a field from the type at `type_index`, along with its values
+    pub fn remove_field(&mut self, type_index: usize, field_index: usize) -> io::Result<RdbnFieldDeclaration> {
+        self.check_field_index(type_index, field_index)?;
+
+        let field = self.types[type_index].fields.remove(field_index);
+
+        self.update_rows(type_index, |row| {
+            row.remove(field_index);
+        });
+        Ok(field)
+    }
+
+    /// Moves a field of the type at `type_index` from `from` to `to`, along with its values
+    pub fn move_field(&mut self, type_index: usize, from: usize, to: usize) -> io::Result<()> {
+        self.check_field_index(type_index, from)?;
+        self.check_field_index(type_index, to)?;
+
+        let field = self.types[type_index].fields.remove(from);
+        self.types[type_index].fields.insert(to, field);
+
+        self.update_rows(type_index, |row| {
+            let values = row.remove(from);
+            row.insert(to, values);
+        });
+        Ok(())
+    }
+
+    /// Changes how many values a field of the type at `type_index` holds.
+    /// Rows keep their first values, and get [`default_value`](RdbnFieldDeclaration::default_value) for the new ones.
+    pub fn set_field_count(&mut self, type_index: usize, field_index: usize, count: i32) -> io::Result<()> {
+        self.check_field_index(type_index, field_index)?;
+
+        let field = &mut self.types[type_index].fields[field_index];
+        if count <= 0 {
+            return Err(invalid_data(format!("Field {} must hold at least one value, not {count}", field.name)))
+        }
+
+        field.count = count;
+        let default = field.default_value();
+
+        self.update_rows(type_index, |row| row[field_index].resize(count as usize, default.clone()));
+        Ok(())
+    }
+
+    /// Changes the type of a field of the type at `type_index`. `size` is only used for the composite and unknown types,
+    /// which have no size of their own, and `None` keeps the size of the field.
+    /// The values are kept when the new type can hold them, and integers are converted to the other integer types they fit in.
+    /// Nothing changes if a row holds a value that can't be converted.
+    pub fn retype_field(&mut self, type_index: usize, field_index: usize, field_type: RdbnFieldType, size: Option<i32>) -> io::Result<()> {
+        self.check_field_index(type_index, field_index)?;
+
+        let field = &self.types[type_index].fields[field_index];
+        let mut retyped = RdbnFieldDeclaration::new(&field.name, field_type, field.count);
+        if field_type.size().is_none() {
+            retyped.size = size.unwrap_or(field.size);
+        }
+        check_field(&retyped)?;
+
+        let mut converted = Vec::new();
+        for list in self.lists.iter().filter(|list| list.type_index == type_index) {
+            for (j, row) in list.values.iter().enumerate() {
+                let values = row[field_index].iter().map(|value| convert_value(value, &retyped).ok_or_else(|| invalid_data(
+                    format!("Row {j} of list {}: field {} of type {:?} cannot hold {:?}", list.name, retyped.name, field_type, value)
+                ))).collect::<io::Result<Vec<_>>>()?;
+
+                converted.push(values);
+            }
+        }
+
+        self.types[type_index].fields[field_index] = retyped;
+
+        let mut converted = converted.into_iter();
+        self.update_rows(type_index, |row| row[field_index] = converted.next().unwrap());
+        Ok(())
+    }
+
+    /// Checks that the type exists and that the rows of its lists hold every field, so they can be edited along with it
+    fn check_rows(&self, type_index: usize) -> io::Result<()> {
+        let type_declaration = self.types.get(type_index).ok_or_else(|| invalid_data(
+            format!("Type {type_index} is out of range, there are only {} types", self.types.len())
+        ))?;
+
+        for list in self.lists.iter().filter(|list| list.type_index == type_index) {
+            if let Some(j) = list.values.iter().position(|row| row.len() != type_declaration.fields.len()) {
+                return Err(invalid_data(format!("Row {j} of list {} has {} fields instead of {}", list.name, list.values[j].len(), type_declaration.fields.len())))
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_field_index(&self, type_index: usize, field_index: usize) -> io::Result<()> {
+        self.check_rows(type_index)?;
+        let type_declaration = &self.types[type_index];
+
+        if field_index >= type_declaration.fields.len() {
+            return Err(invalid_data(format!("Field {field_index} is out of range, type {} has {} fields", type_declaration.name, type_declaration.fields.len())))
+        }
+
+        Ok(())
+    }
+
+    /// Lays out the edited type and applies `edit` to every row of the lists that use it
+    fn update_rows(&mut self, type_index: usize, mut edit: impl FnMut(&mut Vec<Vec<RdbnValue>>)) {
+        let type_declaration = &mut self.types[type_index];
+        let value_size = type_declaration.compute_layout();
+
+        // The fields no longer match the ones the type shared in the field table
+        type_declaration.field_index = None;
+
+        for list in self.lists.iter_mut().filter(|list| list.type_index == type_index) {
+            list.value_size = value_size;
+            list.values.iter_mut().for_each(&mut edit);
+        }
+    }
+}
+
+/// The value in the representation of `field`, if it can hold it without losing anything
+fn convert_value(value: &RdbnValue, field: &RdbnFieldDeclaration) -> Option<RdbnValue> {
+    if field.accepts(value) {
+        return Some(value.clone())
+    }
+
+    let integer = match value {
+        RdbnValue::Bool(v) => *v as i64,
+        RdbnValue::Byte(v) => *v as i64,
+        RdbnValue::Short(v) => *v as i64,
+        RdbnValue::Int(v) => *v as i64,
+        RdbnValue::Long(v) => *v,
+        RdbnValue::UShort(v) => *v as i64,
+        RdbnValue::Uint(v) | RdbnValue::Hash(v) => *v as i64,
+        _ => return None,
+    };
+
+    let converted = match field.field_type {
+        RdbnFieldType::Bool => RdbnValue::Bool(match integer {
+            0 => false,
+            1 => true,
+            _ => return None,
+        }),
+        RdbnFieldType::Byte => RdbnValue::Byte(u8::try_from(integer).ok()?),
+        RdbnFieldType::Short | RdbnFieldType::ActType => RdbnValue::Short(i16::try_from(integer).ok()?),
+        RdbnFieldType::Int | RdbnFieldType::Flag => RdbnValue::Int(i32::try_from(integer).ok()?),
+        RdbnFieldType::Long => RdbnValue::Long(integer),
+        RdbnFieldType::UInt => RdbnValue::Uint(u32::try_from(integer).ok()?),
+        RdbnFieldType::UShort => RdbnValue::UShort(u16::try_from(integer).ok()?),
+        RdbnFieldType::Hash => RdbnValue::Hash(u32::try_from(integer).ok()?),
+        _ => return None,
+    };
+
+    Some(converted)
+}
+
+/// Checks that a new field has a name, holds values, and has the size of its type
+pub(super) fn check_field(field: &RdbnFieldDeclaration) -> io::Result<()> {
+    if field.name.is_empty() {
+        return Err(invalid_data("A field needs a name".to_string()))
+    }
+    if field.count <= 0 {
+        return Err(invalid_data(format!("Field {} must hold at least one value, not {}", field.name, field.count)))
+    }
+    if field.size <= 0 || field.field_type.size().is_some_and(|size| size != field.size) {
+        return Err(invalid_data(format!("Field {} of type {:?} cannot have values of {} bytes", field.name, field.field_type, field.size)))
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -1,4 +1,4 @@
-use super::BinaryReader;
+use super::{BinaryReader, BinaryWriter, CfgBinError, RdbnFieldType};
 
 pub struct RdbnFieldEntry {
     pub(crate) name_hash: u32,
@@ -10,14 +10,32 @@ pub struct RdbnFieldEntry {
 }
 
 impl RdbnFieldEntry {
-    pub fn new(binary_reader: &mut BinaryReader) -> RdbnFieldEntry {
-        RdbnFieldEntry {
-            name_hash: binary_reader.read_u32(),
-            r#type: binary_reader.read_i16(),
-            type_category: binary_reader.read_i16(),
-            value_size: binary_reader.read_i32(),
-            value_offset: binary_reader.read_i32(),
-            value_count: binary_reader.read_i32(),
+    pub fn new(binary_reader: &mut BinaryReader) -> Result<RdbnFieldEntry, CfgBinError> {
+        Ok(RdbnFieldEntry {
+            name_hash: binary_reader.read_u32()?,
+            r#type: binary_reader.read_i16()?,
+            type_category: binary_reader.read_i16()?,
+            value_size: binary_reader.read_i32()?,
+            value_offset: binary_reader.read_i32()?,
+            value_count: binary_reader.read_i32()?,
+        })
+    }
+
+    /// The type of the values. A field whose size is not the one of its type can't be read with it,
+    /// so its values are kept as raw bytes like the ones of an unknown type.
+    pub fn field_type(&self) -> RdbnFieldType {
+        match RdbnFieldType::from(self.r#type) {
+            field_type if field_type.size().is_some_and(|size| size != self.value_size) => RdbnFieldType::Unknown(self.r#type),
+            field_type => field_type,
         }
     }
-}
+
+    pub fn write(&self, binary_writer: &mut BinaryWriter) {
+        binary_writer.write_u32(self.name_hash);
+        binary_writer.write_i16(self.r#type);
+        binary_writer.write_i16(self.type_category);
+        binary_writer.write_i32(self.value_size);
+        binary_writer.write_i32(self.value_offset);
+        binary_writer.write_i32(self.value_count);
+    }
+}
\ No newline at end of file
@@ -1,50 +1,118 @@
 use serde::{Deserialize, Serialize};
 
-#[repr(i16)]
+/// The type of the values of an RDBN field, stored as an ID in the field entries
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum RdbnFieldType {
-    AbilityData = 0,
-    EnhanceData = 1,
-    StatusRate = 2,
-    Bool = 3,
-    Byte = 4,
-    Short = 5,
-    Int = 6,
+    AbilityData,
+    EnhanceData,
+    StatusRate,
+    Bool,
+    Byte,
+    Short,
+    Int,
+    /// A signed 8 byte integer
+    Long,
+    /// An unsigned 4 byte integer
+    UInt,
 
-    ActType = 9,
-    Flag = 10,
+    ActType,
+    Flag,
+    /// An unsigned 2 byte integer
+    UShort,
+    /// An 8 byte float
+    Double,
 
-    Float = 13,
+    Float,
+    /// Two floats
+    Float2,
 
-    Hash = 15,
+    Hash,
+    /// Three floats
+    Float3,
+    /// Two signed 4 byte integers
+    Int2,
 
-    RateMatrix = 18,
-    Position = 19,
-    String = 20,
-    DataTuple = 21
+    RateMatrix,
+    Position,
+    String,
+    DataTuple,
+
+    /// A type ID whose values have no known meaning. Its values are kept as raw bytes of the field size.
+    Unknown(i16),
+}
+
+impl RdbnFieldType {
+    /// The size in bytes of a single value, or `None` for the composite and unknown types whose size depends on the field
+    pub fn size(self) -> Option<i32> {
+        match self {
+            RdbnFieldType::AbilityData | RdbnFieldType::EnhanceData | RdbnFieldType::StatusRate | RdbnFieldType::Unknown(_) => None,
+            RdbnFieldType::Byte => Some(1),
+            RdbnFieldType::Short | RdbnFieldType::ActType | RdbnFieldType::UShort => Some(2),
+            RdbnFieldType::Bool | RdbnFieldType::Int | RdbnFieldType::UInt | RdbnFieldType::Flag | RdbnFieldType::Float
+                | RdbnFieldType::Hash | RdbnFieldType::String | RdbnFieldType::DataTuple => Some(4),
+            RdbnFieldType::Long | RdbnFieldType::Double | RdbnFieldType::Float2 | RdbnFieldType::Int2 => Some(8),
+            RdbnFieldType::Float3 => Some(12),
+            RdbnFieldType::RateMatrix | RdbnFieldType::Position => Some(16),
+        }
+    }
 }
 
-impl TryFrom<i16> for RdbnFieldType {
-    type Error = ();
+impl From<i16> for RdbnFieldType {
+    fn from(value: i16) -> Self {
+        match value {
+            0 => RdbnFieldType::AbilityData,
+            1 => RdbnFieldType::EnhanceData,
+            2 => RdbnFieldType::StatusRate,
+            3 => RdbnFieldType::Bool,
+            4 => RdbnFieldType::Byte,
+            5 => RdbnFieldType::Short,
+            6 => RdbnFieldType::Int,
+            7 => RdbnFieldType::Long,
+            8 => RdbnFieldType::UInt,
+            9 => RdbnFieldType::ActType,
+            10 => RdbnFieldType::Flag,
+            0xB => RdbnFieldType::UShort,
+            0xC => RdbnFieldType::Double,
+            0xD => RdbnFieldType::Float,
+            0xE => RdbnFieldType::Float2,
+            0xF => RdbnFieldType::Hash,
+            0x10 => RdbnFieldType::Float3,
+            0x11 => RdbnFieldType::Int2,
+            0x12 => RdbnFieldType::RateMatrix,
+            0x13 => RdbnFieldType::Position,
+            0x14 => RdbnFieldType::String,
+            0x15 => RdbnFieldType::DataTuple,
+            _ => RdbnFieldType::Unknown(value),
+        }
+    }
+}
 
-    fn try_from(value: i16) -> Result<Self, Self::Error> {
+impl From<RdbnFieldType> for i16 {
+    fn from(value: RdbnFieldType) -> Self {
         match value {
-            0 => Ok(RdbnFieldType::AbilityData),
-            1 => Ok(RdbnFieldType::EnhanceData),
-            2 => Ok(RdbnFieldType::StatusRate),
-            3 => Ok(RdbnFieldType::Bool),
-            4 => Ok(RdbnFieldType::Byte),
-            5 => Ok(RdbnFieldType::Short),
-            6 => Ok(RdbnFieldType::Int),
-            9 => Ok(RdbnFieldType::ActType),
-            10 => Ok(RdbnFieldType::Flag),
-            0xD => Ok(RdbnFieldType::Float),
-            0xF => Ok(RdbnFieldType::Hash),
-            0x12 => Ok(RdbnFieldType::RateMatrix),
-            0x13 => Ok(RdbnFieldType::Position),
-            0x14 => Ok(RdbnFieldType::String),
-            0x15 => Ok(RdbnFieldType::DataTuple),
-            _ => Err(()),
+            RdbnFieldType::AbilityData => 0,
+            RdbnFieldType::EnhanceData => 1,
+            RdbnFieldType::StatusRate => 2,
+            RdbnFieldType::Bool => 3,
+            RdbnFieldType::Byte => 4,
+            RdbnFieldType::Short => 5,
+            RdbnFieldType::Int => 6,
+            RdbnFieldType::Long => 7,
+            RdbnFieldType::UInt => 8,
+            RdbnFieldType::ActType => 9,
+            RdbnFieldType::Flag => 10,
+            RdbnFieldType::UShort => 0xB,
+            RdbnFieldType::Double => 0xC,
+            RdbnFieldType::Float => 0xD,
+            RdbnFieldType::Float2 => 0xE,
+            RdbnFieldType::Hash => 0xF,
+            RdbnFieldType::Float3 => 0x10,
+            RdbnFieldType::Int2 => 0x11,
+            RdbnFieldType::RateMatrix => 0x12,
+            RdbnFieldType::Position => 0x13,
+            RdbnFieldType::String => 0x14,
+            RdbnFieldType::DataTuple => 0x15,
+            RdbnFieldType::Unknown(value) => value,
         }
     }
 }
\ No newline at end of file
@@ -1,10 +1,26 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+use serde::{Deserialize, Serialize};
+
+use super::field_type::RdbnFieldType;
+
+/// The category stored next to the type ID of an RDBN field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum RdbnFieldTypeCategory {
     Primitive = 1,
     Special = 2,
     Composite = 3
 }
 
+impl From<RdbnFieldType> for RdbnFieldTypeCategory {
+    fn from(value: RdbnFieldType) -> Self {
+        match value {
+            RdbnFieldType::AbilityData | RdbnFieldType::EnhanceData | RdbnFieldType::StatusRate => RdbnFieldTypeCategory::Composite,
+            RdbnFieldType::Float2 | RdbnFieldType::Float3 | RdbnFieldType::Int2
+                | RdbnFieldType::RateMatrix | RdbnFieldType::Position | RdbnFieldType::String | RdbnFieldType::DataTuple => RdbnFieldTypeCategory::Special,
+            _ => RdbnFieldTypeCategory::Primitive,
+        }
+    }
+}
+
 impl TryFrom<i16> for RdbnFieldTypeCategory {
     type Error = ();
 
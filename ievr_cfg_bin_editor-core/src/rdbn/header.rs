@@ -1,12 +1,17 @@
-use super::BinaryReader;
+use serde::{Deserialize, Serialize};
+
+use super::{BinaryReader, BinaryWriter, CfgBinError};
+
+const RESERVED_SIZE: usize = 0x14;
 
 #[derive(Debug)]
 pub struct RdbnHeader {
     pub(crate) magic: u32,
-    pub(crate) _header_size: i16,
-    pub(crate) _version: i32,
+    pub(crate) header_size: i16,
+    pub(crate) version: i32,
     pub(crate) data_offset: i16,
-    pub(crate) _data_size: i32,
+    pub(crate) data_size: i32,
+    pub(crate) reserved: [u8; RESERVED_SIZE],
 
     pub(crate) type_offset: i16,
     pub(crate) type_count: i16,
@@ -22,34 +27,35 @@ pub struct RdbnHeader {
 }
 
 impl RdbnHeader {
-    pub fn new(binary_reader: &mut BinaryReader) -> RdbnHeader {
-        let magic       = binary_reader.read_u32();
-        let _header_size = binary_reader.read_i16();
-        let _version     = binary_reader.read_i32();
-        let data_offset = binary_reader.read_i16();
-        let _data_size   = binary_reader.read_i32();
+    pub fn new(binary_reader: &mut BinaryReader) -> Result<RdbnHeader, CfgBinError> {
+        let magic       = binary_reader.read_u32()?;
+        let header_size = binary_reader.read_i16()?;
+        let version     = binary_reader.read_i32()?;
+        let data_offset = binary_reader.read_i16()?;
+        let data_size   = binary_reader.read_i32()?;
 
-        // Skip 0x14 bytes (unknown / reserved)
-        binary_reader.skip(0x14);
+        // 0x14 bytes (unknown / reserved)
+        let reserved = binary_reader.read_bytes(RESERVED_SIZE)?.try_into().unwrap();
 
-        let type_offset             = binary_reader.read_i16();
-        let type_count              = binary_reader.read_i16();
-        let field_offset            = binary_reader.read_i16();
-        let field_count             = binary_reader.read_i16();
-        let root_offset             = binary_reader.read_i16();
-        let root_count              = binary_reader.read_i16();
-        let string_hash_offset      = binary_reader.read_i16();
-        let string_offsets_offset   = binary_reader.read_i16();
-        let hash_count              = binary_reader.read_i16();
-        let value_offset            = binary_reader.read_i16();
-        let string_offset           = binary_reader.read_i32();
+        let type_offset             = binary_reader.read_i16()?;
+        let type_count              = binary_reader.read_i16()?;
+        let field_offset            = binary_reader.read_i16()?;
+        let field_count             = binary_reader.read_i16()?;
+        let root_offset             = binary_reader.read_i16()?;
+        let root_count              = binary_reader.read_i16()?;
+        let string_hash_offset      = binary_reader.read_i16()?;
+        let string_offsets_offset   = binary_reader.read_i16()?;
+        let hash_count              = binary_reader.read_i16()?;
+        let value_offset            = binary_reader.read_i16()?;
+        let string_offset           = binary_reader.read_i32()?;
 
-        RdbnHeader {
+        Ok(RdbnHeader {
             magic,
-            _header_size,
-            _version,
+            header_size,
+            version,
             data_offset,
-            _data_size,
+            data_size,
+            reserved,
 
             type_offset,
             type_count,
@@ -62,6 +68,62 @@ impl RdbnHeader {
             hash_count,
             value_offset,
             string_offset,
+        })
+    }
+
+    pub fn write(&self, binary_writer: &mut BinaryWriter) {
+        binary_writer.write_u32(self.magic);
+        binary_writer.write_i16(self.header_size);
+        binary_writer.write_i32(self.version);
+        binary_writer.write_i16(self.data_offset);
+        binary_writer.write_i32(self.data_size);
+
+        // 0x14 bytes (unknown / reserved)
+        binary_writer.write_bytes(&self.reserved);
+
+        binary_writer.write_i16(self.type_offset);
+        binary_writer.write_i16(self.type_count);
+        binary_writer.write_i16(self.field_offset);
+        binary_writer.write_i16(self.field_count);
+        binary_writer.write_i16(self.root_offset);
+        binary_writer.write_i16(self.root_count);
+        binary_writer.write_i16(self.string_hash_offset);
+        binary_writer.write_i16(self.string_offsets_offset);
+        binary_writer.write_i16(self.hash_count);
+        binary_writer.write_i16(self.value_offset);
+        binary_writer.write_i32(self.string_offset);
+    }
+}
+
+/// The header values whose meaning is unknown. They are kept so that a file can be written back unchanged.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RdbnOpaqueHeader {
+    pub header_size: i16,
+    pub version: i32,
+    /// Written back as is when set, computed from the written file otherwise.
+    /// It is only kept when it doesn't match the size of the file it was read from.
+    pub data_size: Option<i32>,
+    pub reserved: [u8; RESERVED_SIZE],
+}
+
+impl Default for RdbnOpaqueHeader {
+    fn default() -> Self {
+        RdbnOpaqueHeader {
+            header_size: super::MINIMUM_SIZE as i16,
+            version: 0,
+            data_size: None,
+            reserved: [0; RESERVED_SIZE],
+        }
+    }
+}
+
+impl From<&RdbnHeader> for RdbnOpaqueHeader {
+    fn from(header: &RdbnHeader) -> Self {
+        RdbnOpaqueHeader {
+            header_size: header.header_size,
+            version: header.version,
+            data_size: Some(header.data_size),
+            reserved: header.reserved,
         }
     }
 }
\ No newline at end of file
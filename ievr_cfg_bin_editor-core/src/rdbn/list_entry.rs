@@ -1,21 +1,40 @@
-#[derive(Debug)]
+/// A list of rows, all of the same type
+#[derive(Debug, Clone, PartialEq)]
 pub struct RdbnListEntry {
     pub name: String,
+    /// The index of the row type in [`Rdbn::types`](super::Rdbn::types)
     pub type_index: usize,
+    /// The size of a row in bytes
+    pub value_size: i32,
+    /// Unknown value of the root entry, kept to write the file back unchanged
+    pub unk1: i16,
+    /// The rows, each holding the values of every field of the type, as many as the field count
     pub values: Vec<Vec<Vec<RdbnValue>>>,
 }
 
-#[derive(Debug)]
+/// A value of an RDBN field
+#[derive(Debug, Clone, PartialEq)]
 pub enum RdbnValue {
     Bool(bool),
     Byte(u8),
     Short(i16),
     Int(i32),
+    Long(i64),
+    /// An unsigned integer, or a hash read without its meaning
     Uint(u32),
+    UShort(u16),
     Float(f32),
+    Double(f64),
     Hash(u32),
     String(String),
+    /// A string field that points to no string
+    NullString,
+    /// A string field whose offset is outside of the string table, kept as it is since some files store other values there
+    StringOffset(u32),
     Bytes(Vec<u8>),
+    Float2([f32; 2]),
+    Float3([f32; 3]),
     Float4([f32; 4]),
     Short2([i16; 2]),
+    Int2([i32; 2]),
 }
\ No newline at end of file
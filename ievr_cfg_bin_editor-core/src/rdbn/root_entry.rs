@@ -1,8 +1,8 @@
-use super::BinaryReader;
+use super::{BinaryReader, BinaryWriter, CfgBinError};
 
 pub struct RdbnRootEntry {
     pub(crate) type_index: i16,
-    _unk1: i16,
+    pub(crate) unk1: i16,
     pub(crate) value_offset: i32,
     pub(crate) value_size: i32,
     pub(crate) value_count: i32,
@@ -10,14 +10,23 @@ pub struct RdbnRootEntry {
 }
 
 impl RdbnRootEntry {
-    pub fn new(binary_reader: &mut BinaryReader) -> RdbnRootEntry {
-        RdbnRootEntry { 
-            type_index: binary_reader.read_i16(),
-            _unk1: binary_reader.read_i16(),
-            value_offset: binary_reader.read_i32(),
-            value_size: binary_reader.read_i32(),
-            value_count: binary_reader.read_i32(),
-            name_hash: binary_reader.read_u32(),
-        }
+    pub fn new(binary_reader: &mut BinaryReader) -> Result<RdbnRootEntry, CfgBinError> {
+        Ok(RdbnRootEntry { 
+            type_index: binary_reader.read_i16()?,
+            unk1: binary_reader.read_i16()?,
+            value_offset: binary_reader.read_i32()?,
+            value_size: binary_reader.read_i32()?,
+            value_count: binary_reader.read_i32()?,
+            name_hash: binary_reader.read_u32()?,
+        })
+    }
+
+    pub fn write(&self, binary_writer: &mut BinaryWriter) {
+        binary_writer.write_i16(self.type_index);
+        binary_writer.write_i16(self.unk1);
+        binary_writer.write_i32(self.value_offset);
+        binary_writer.write_i32(self.value_size);
+        binary_writer.write_i32(self.value_count);
+        binary_writer.write_u32(self.name_hash);
     }
 }
\ No newline at end of file
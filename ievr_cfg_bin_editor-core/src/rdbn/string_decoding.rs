@@ -0,0 +1,39 @@
+use super::{CfgBinError, RdbnString, RdbnValue};
+
+/// How RDBN strings that are not valid UTF-8 are read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StringDecoding {
+    /// Fails the whole parse with [`CfgBinError::InvalidString`], or [`CfgBinError::StringOffsetOutOfRange`]
+    /// for string fields that point outside of the string table. The other modes read those as [`RdbnValue::StringOffset`].
+    Strict,
+    /// Replaces the invalid bytes with U+FFFD. The original bytes are lost.
+    Lossy,
+    /// Keeps string values as raw bytes so that they are written back unchanged.
+    /// Names are shown lossily, and their bytes are kept in the string table along with their hash.
+    #[default]
+    Raw,
+}
+
+impl StringDecoding {
+    /// Decodes a type, field or list name of the string table
+    pub(crate) fn decode_name(self, hash: u32, bytes: &[u8], offset: usize, section: &'static str) -> Result<RdbnString, CfgBinError> {
+        let (name, raw) = match (self, std::str::from_utf8(bytes)) {
+            (_, Ok(name)) => (name.to_string(), None),
+            (StringDecoding::Strict, Err(_)) => return Err(CfgBinError::InvalidString { offset, section, encoding: "UTF-8" }),
+            (StringDecoding::Lossy, Err(_)) => (String::from_utf8_lossy(bytes).into_owned(), None),
+            (StringDecoding::Raw, Err(_)) => (String::from_utf8_lossy(bytes).into_owned(), Some(bytes.to_vec())),
+        };
+
+        Ok(RdbnString { hash, name, raw })
+    }
+
+    /// Decodes the value of a string field
+    pub(crate) fn decode_value(self, bytes: &[u8], offset: usize, section: &'static str) -> Result<RdbnValue, CfgBinError> {
+        match (self, std::str::from_utf8(bytes)) {
+            (_, Ok(value)) => Ok(RdbnValue::String(value.to_string())),
+            (StringDecoding::Strict, Err(_)) => Err(CfgBinError::InvalidString { offset, section, encoding: "UTF-8" }),
+            (StringDecoding::Lossy, Err(_)) => Ok(RdbnValue::String(String::from_utf8_lossy(bytes).into_owned())),
+            (StringDecoding::Raw, Err(_)) => Ok(RdbnValue::Bytes(bytes.to_vec())),
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,72 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::t2b::HashType;
+
+use super::StringPool;
+
+/// An entry of the string table of an RDBN file: a name and the hash that the entries use to reference it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RdbnString {
+    pub hash: u32,
+    pub name: String,
+    /// The bytes of a name that is not valid UTF-8, read with [`StringDecoding::Raw`](super::StringDecoding::Raw).
+    /// `name` only shows them lossily, so they are written instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<Vec<u8>>,
+}
+
+/// Builds the hash table of a file being written. It starts with the string table the file was read from,
+/// in the same order and with the same hashes, and only the names that are not in there are hashed and added.
+/// The names found in a dictionary keep their hash without being added.
+pub(super) struct StringTableWriter<'a> {
+    /// (name hash, string offset), in the order of the table
+    entries: Vec<(u32, u32)>,
+    hashes: HashMap<&'a str, u32>,
+    /// The hash of the names of the table, which the added names use too
+    hash_type: HashType,
+}
+
+impl<'a> StringTableWriter<'a> {
+    pub fn new(strings: &'a [RdbnString], dictionary_names: &'a BTreeMap<u32, String>, pool: &mut StringPool) -> StringTableWriter<'a> {
+        let mut hashes = HashMap::with_capacity(strings.len() + dictionary_names.len());
+        let entries = strings.iter().map(|string| {
+            hashes.entry(string.name.as_str()).or_insert(string.hash);
+            (string.hash, pool.add(string.raw.as_deref().unwrap_or(string.name.as_bytes())))
+        }).collect();
+
+        for (hash, name) in dictionary_names {
+            hashes.entry(name.as_str()).or_insert(*hash);
+        }
+
+        StringTableWriter { entries, hashes, hash_type: detect_hash_type(strings) }
+    }
+
+    /// The hash of a name, which is added to the table if it isn't in there yet
+    pub fn hash(&mut self, name: &'a str, pool: &mut StringPool) -> u32 {
+        if let Some(hash) = self.hashes.get(name) {
+            return *hash
+        }
+
+        let hash = self.hash_type.compute(name.as_bytes());
+        self.entries.push((hash, pool.add(name.as_bytes())));
+        self.hashes.insert(name, hash);
+
+        hash
+    }
+
+    pub fn entries(&self) -> &[(u32, u32)] {
+        &self.entries
+    }
+}
+
+/// The hash of the first name of the table that is hashed with CRC32 or CRC32/JAM.
+/// The new files, and the tables where no name matches either, use CRC32.
+fn detect_hash_type(strings: &[RdbnString]) -> HashType {
+    strings.iter().find_map(|string| {
+        let name = string.raw.as_deref().unwrap_or(string.name.as_bytes());
+        [HashType::Crc32Standard, HashType::Crc32Jam].into_iter().find(|hash_type| hash_type.compute(name) == string.hash)
+    })
+    .unwrap_or(HashType::Crc32Standard)
+}
\ No newline at end of file
@@ -1,4 +1,4 @@
-use super::BinaryReader;
+use super::{BinaryReader, BinaryWriter, CfgBinError};
 
 pub struct RdbnTypeEntry {
     pub(crate) name_hash: u32,
@@ -8,12 +8,19 @@ pub struct RdbnTypeEntry {
 }
 
 impl RdbnTypeEntry {
-    pub fn new(binary_reader: &mut BinaryReader) -> RdbnTypeEntry {
-        RdbnTypeEntry {
-            name_hash: binary_reader.read_u32(),
-            unk1: binary_reader.read_u32(),
-            field_index: binary_reader.read_i16(),
-            field_count: binary_reader.read_i16(),
-        }
+    pub fn new(binary_reader: &mut BinaryReader) -> Result<RdbnTypeEntry, CfgBinError> {
+        Ok(RdbnTypeEntry {
+            name_hash: binary_reader.read_u32()?,
+            unk1: binary_reader.read_u32()?,
+            field_index: binary_reader.read_i16()?,
+            field_count: binary_reader.read_i16()?,
+        })
     }
-}
+
+    pub fn write(&self, binary_writer: &mut BinaryWriter) {
+        binary_writer.write_u32(self.name_hash);
+        binary_writer.write_u32(self.unk1);
+        binary_writer.write_i16(self.field_index);
+        binary_writer.write_i16(self.field_count);
+    }
+}
\ No newline at end of file
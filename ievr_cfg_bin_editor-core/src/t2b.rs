@@ -1,115 +1,169 @@
-use std::collections::HashMap;
+use std::{collections::{BTreeMap, HashMap, HashSet}, io::{self, Write}};
 
-use crc_fast::{CrcParams, checksum_with_params};
+use serde::{Deserialize, Serialize};
 
-use crate::{common::binary_reader::BinaryReader, t2b::{checksum_section::T2bChecksumEntry, entry_section::{ValueLength}}};
+use crate::{error::CfgBinError, dictionary::HashDictionary, common::{invalid_data, binary_reader::BinaryReader, binary_writer::BinaryWriter, hash::{compute_crc32_jam, compute_crc32_standard}, string_pool::StringPool}, t2b::checksum_section::T2bChecksumEntry};
 
 pub use crate::{
-    t2b::entry_section::T2bValueType,
+    t2b::entry_section::{T2bValueType, ValueLength},
+    t2b::encoding::T2bEncoding,
+    t2b::tree::{T2bBlock, T2bNode, T2bTree},
+    t2b::builder::T2bBuilder,
 };
 
 mod footer;
 mod entry_section;
 mod checksum_section;
+mod encoding;
+mod tree;
+mod builder;
 
-use footer::T2bFooter;
+use footer::{T2bFooter, T2B_MAGIC, FOOTER_SIZE};
 use entry_section::T2bEntrySection;
 use checksum_section::T2bChecksumSection;
 
-const MINIMUM_SIZE: i32 = 0x30;
+const MINIMUM_SIZE: usize = 0x30;
 
+/// A T2B file: a list of named entries, each holding a few string, integer or floating point values.
+///
+/// This is the low-level view of the file. [`Database`](crate::Database) groups the entries by name into tables.
+#[derive(Debug, Clone)]
 pub struct T2b {
+    /// The entries in the order of the file, starting with the one that holds the size of the file
     pub entries: Vec<T2bEntry>,
-    pub encoding: i16,
+    pub encoding: T2bEncoding,
     pub value_length: ValueLength,
     pub hash_type: HashType,
+    pub opaque: T2bOpaqueFields,
+    /// The entry names that the checksum section has no string for, found in a dictionary when reading.
+    /// The writer keeps their checksum and doesn't add them to the checksum section.
+    pub dictionary_names: BTreeMap<u32, String>,
 }
 
-impl T2b {
-    pub fn read(file: &[u8]) -> Option<T2b> {
-        let mut binary_reader = BinaryReader::new(file);
+/// The values whose meaning is unknown. They are kept so that a file can be written back unchanged.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct T2bOpaqueFields {
+    pub footer_unk1: i16,
+    pub footer_unk2: i16,
+    /// Written back as is when set, counted from the written strings otherwise.
+    /// It is only kept when it doesn't match the number of strings of the file it was read from.
+    pub string_data_count: Option<u32>,
+    /// Whether the first value of the leading entry is the size of the file, in which case it is computed again when writing.
+    /// It is only unset when that value didn't match the size of the file it was read from.
+    #[serde(default = "default_size_entry")]
+    pub size_entry: bool,
+}
+
+/// The exports made before the flag existed always had their size entry computed again
+fn default_size_entry() -> bool {
+    true
+}
 
-        if binary_reader.file_size() < MINIMUM_SIZE as usize {
-            return None;
+impl Default for T2bOpaqueFields {
+    fn default() -> Self {
+        T2bOpaqueFields {
+            footer_unk1: 0x1FE,
+            footer_unk2: 1,
+            string_data_count: None,
+            size_entry: true,
         }
+    }
+}
 
-        binary_reader.set_position(binary_reader.file_size() - 0x10);
+impl T2b {
+    /// Reads a T2B file
+    pub fn read(file: &[u8]) -> Result<T2b, CfgBinError> {
+        Self::read_with_dictionary(file, &HashDictionary::new())
+    }
 
-        let footer = T2bFooter::read(&mut binary_reader);
-        if footer.magic != 0x62327401 { // .t2b in little-endian
-            return None;
-        }
+    /// Reads a T2B file, naming the entries whose checksum has no string in the file with `dictionary`
+    pub fn read_with_dictionary(file: &[u8], dictionary: &HashDictionary) -> Result<T2b, CfgBinError> {
+        let mut binary_reader = BinaryReader::new(file);
 
-        binary_reader.set_position(0);
+        binary_reader.ensure_available(0, MINIMUM_SIZE, "T2B file")?;
 
-        let entry_section = match T2bEntrySection::read(&mut binary_reader) {
-            Some(data) => data,
-            None => return None
-        };
+        let footer_position = binary_reader.file_size() - FOOTER_SIZE;
+        let footer = T2bFooter::read(&mut binary_reader.window(footer_position, FOOTER_SIZE, "T2B footer")?)?;
+        if footer.magic != T2B_MAGIC {
+            return Err(CfgBinError::BadMagic { offset: footer_position, section: "T2B footer", magic: footer.magic });
+        }
+
+        let entry_section = T2bEntrySection::read(&mut binary_reader)?;
 
         // Read value string
         let value_string_data = if entry_section.string_size > 0 {
-            binary_reader.set_position(entry_section.string_offset as usize);
-            binary_reader.read_bytes(entry_section.string_size as usize).to_vec()
+            binary_reader.set_position(entry_section.string_offset as usize)?;
+            binary_reader.read_bytes(entry_section.string_size as usize)?.to_vec()
         } else {
             Vec::new()
         };
 
-        binary_reader.seek_alignment(0x10);
+        binary_reader.seek_alignment(0x10)?;
 
-        let encoding = footer.encoding;
-        let checksum_position = binary_reader.position();
+        let encoding = T2bEncoding::from(footer.encoding);
 
         let checksum_section = T2bChecksumSection::read(&mut binary_reader)?;
 
-        binary_reader.set_position(checksum_position + checksum_section.string_offset as usize);
-
         let checksum_string_data = if checksum_section.string_size > 0 {
-            binary_reader.set_position(checksum_section.string_offset as usize);
-            binary_reader.read_bytes(checksum_section.string_size as usize)
+            binary_reader.set_position(checksum_section.string_offset as usize)?;
+            binary_reader.read_bytes(checksum_section.string_size as usize)?
         } else { &[] };
 
         let mut hash_type = HashType::Crc32Standard;
 
-        if checksum_section.checksum_entries.len() > 0 {
-            if let Some(hash) = try_detect_hash_type(&checksum_section.checksum_entries[0], checksum_string_data, encoding) {
-                hash_type = hash;
-            } else {
-                return None
+        if let Some(first_entry) = checksum_section.checksum_entries.first() {
+            match try_detect_hash_type(first_entry, checksum_string_data, checksum_section.string_offset as usize) {
+                Some(hash) => hash_type = hash,
+                None => return Err(CfgBinError::HashMismatch { offset: first_entry.position, section: "T2B checksum section", hash: first_entry.crc }),
             }
         }
 
-        // println!("{:?}", entry_section);
+        // A string count that matches the string data is counted again when writing, so that it follows the edits
+        let string_count = value_string_data.iter().filter(|byte| **byte == 0).count();
+        let opaque = T2bOpaqueFields {
+            footer_unk1: footer.unk1,
+            footer_unk2: footer.unk2,
+            string_data_count: (entry_section.string_count as usize != string_count).then_some(entry_section.string_count),
+            size_entry: false,
+        };
+
+        let mut t2b = T2b::create_configuration(entry_section, checksum_section, &value_string_data, checksum_string_data, encoding, hash_type, opaque, dictionary)?;
+
+        // A leading value that matches the size of the file is computed again when writing, so that it follows the edits
+        t2b.opaque.size_entry = t2b.entries.first().and_then(|entry| entry.values.first()) == Some(&size_value(file.len(), t2b.value_length));
 
-        Some(T2b::create_configuration(entry_section, checksum_section, &value_string_data, checksum_string_data, encoding, hash_type))
+        Ok(t2b)
     }
 
-    fn create_configuration(entry_section: T2bEntrySection, checksum_section: T2bChecksumSection, value_string_data: &[u8], checksum_string_data: &[u8], encoding: i16, hash_type: HashType) -> T2b {
-        let checksum_offset_lookup: HashMap<u32, u32> = checksum_section.checksum_entries.iter().map(|section| {
-            (section.crc, section.string_offset - checksum_section.checksum_entries[0].string_offset)
+    #[allow(clippy::too_many_arguments)]
+    fn create_configuration(entry_section: T2bEntrySection, checksum_section: T2bChecksumSection, value_string_data: &[u8], checksum_string_data: &[u8], encoding: T2bEncoding, hash_type: HashType, opaque: T2bOpaqueFields, dictionary: &HashDictionary) -> Result<T2b, CfgBinError> {
+        let first_string_offset = checksum_section.checksum_entries.first().map_or(0, |entry| entry.string_offset as i64);
+        let checksum_offset_lookup: HashMap<u32, i64> = checksum_section.checksum_entries.iter().map(|section| {
+            (section.crc, section.string_offset as i64 - first_string_offset)
         })
         .collect();
 
-        let mut config_entries = Vec::with_capacity(entry_section.entries.len());
+        let value_strings_position = entry_section.string_offset as usize;
+        let checksum_strings_position = checksum_section.string_offset as usize;
 
-        for i in 0..entry_section.entries.len() {
-            let mut config_entry_value = Vec::with_capacity(entry_section.entries[i].entry_count as usize);
+        let mut config_entries = Vec::with_capacity(entry_section.entries.len());
+        let mut dictionary_names = BTreeMap::new();
 
-            for j in 0..entry_section.entries[i].entry_count as usize {
-                let entry_type = entry_section.entries[i].entry_types[j];
-                let entry_value = entry_section.entries[i].entry_values[j];
+        for entry in &entry_section.entries {
+            let mut config_entry_value = Vec::with_capacity(entry.entry_count as usize);
 
+            for (&entry_type, &entry_value) in entry.entry_types.iter().zip(&entry.entry_values) {
                 let value = match entry_type {
                     T2bValueType::String => {
                         if entry_value < 0 {
                             T2bValue::String(String::new())
                         } else {
-                            T2bValue::String(read_string(value_string_data, entry_value as u32, encoding).unwrap())
+                            T2bValue::String(read_string(value_string_data, entry_value, encoding, value_strings_position, "T2B value strings")?)
                         }
                     },
                     T2bValueType::Integer => match entry_section.value_length {
                         ValueLength::Int => T2bValue::Integer(entry_value as i32),
-                        ValueLength::Long => T2bValue::Long(entry_value as i64),
+                        ValueLength::Long => T2bValue::Long(entry_value),
                     },
                     T2bValueType::FloatingPoint => {
                         match entry_section.value_length {
@@ -117,7 +171,7 @@ impl T2b {
                             ValueLength::Long => T2bValue::F64(f64::from_bits(entry_value as u64)),
                         }
                     },
-                    T2bValueType::Invalid => panic!("Encountered an invalid value type!"),
+                    T2bValueType::Invalid => return Err(CfgBinError::InvalidValueType { offset: entry.position + 4, section: "T2B entry section" }),
                 };
                 config_entry_value.push(
                     T2bEntryValue {
@@ -127,7 +181,14 @@ impl T2b {
                 );
             }
 
-            let name = read_string(checksum_string_data, checksum_offset_lookup[&entry_section.entries[i].crc32], encoding).unwrap();
+            let name = match (checksum_offset_lookup.get(&entry.crc32), dictionary.get(entry.crc32)) {
+                (Some(name_offset), _) => read_string(checksum_string_data, *name_offset, encoding, checksum_strings_position, "T2B checksum strings")?,
+                (None, Some(name)) => {
+                    dictionary_names.insert(entry.crc32, name.to_string());
+                    name.to_string()
+                },
+                (None, None) => return Err(CfgBinError::UnknownHash { offset: entry.position, section: "T2B entry section", hash: entry.crc32 }),
+            };
 
             config_entries.push(
                 T2bEntry {
@@ -137,87 +198,218 @@ impl T2b {
             );
         }
 
-        T2b {
+        Ok(T2b {
             entries: config_entries,
             encoding,
             value_length: entry_section.value_length,
             hash_type,
+            opaque,
+            dictionary_names,
+        })
+    }
+
+    /// The entries grouped in their `_BEGIN`/`_END` blocks
+    pub fn tree(&self) -> T2bTree {
+        T2bTree::from_entries(self.entries.clone())
+    }
+
+    /// Replaces the entries by the ones of the tree, with updated block counts
+    pub fn set_tree(&mut self, tree: T2bTree) {
+        self.entries = tree.into_entries();
+    }
+
+    pub fn write<W: Write>(&self, output: &mut W) -> io::Result<()> {
+        output.write_all(&self.to_bytes()?)
+    }
+
+    /// Encodes the file. The checksums of the names are computed with the hash type of the file, except for the dictionary names,
+    /// and the integer value of the first entry is set to the size of the written file.
+    pub fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        let mut value_strings = StringPool::new();
+        let mut checksum_strings = StringPool::new();
+
+        let mut checksum_entries = Vec::new();
+        let mut known_checksums = HashSet::new();
+
+        let dictionary_checksums: HashMap<&str, u32> = self.dictionary_names.iter().map(|(hash, name)| (name.as_str(), *hash)).collect();
+
+        let mut entries = Vec::with_capacity(self.entries.len());
+
+        for entry in &self.entries {
+            let name = self.encoding.encode(&entry.name).ok_or_else(|| invalid_data(
+                format!("Entry name {} cannot be encoded in {}", entry.name, self.encoding.name())
+            ))?;
+            let dictionary_checksum = dictionary_checksums.get(entry.name.as_str()).copied();
+            let crc32 = dictionary_checksum.unwrap_or_else(|| self.hash_type.compute(&name));
+
+            if dictionary_checksum.is_none() && known_checksums.insert(crc32) {
+                checksum_entries.push(T2bChecksumEntry {
+                    position: 0,
+                    crc: crc32,
+                    string_offset: checksum_strings.add(&name),
+                });
+            }
+
+            let entry_count = u8::try_from(entry.values.len()).map_err(|_| invalid_data(
+                format!("Entry {} has {} values, but at most {} are supported", entry.name, entry.values.len(), u8::MAX)
+            ))?;
+
+            let entry_values = entry.values.iter()
+                .map(|value| encode_value(value, self.value_length, self.encoding, &mut value_strings)
+                    .ok_or_else(|| invalid_data(format!("Entry {} cannot store {:?} as {:?} in {}", entry.name, value.value, value.r#type, self.encoding.name())))
+                )
+                .collect::<io::Result<Vec<i64>>>()?;
+
+            entries.push(entry_section::T2bEntry {
+                position: 0,
+                crc32,
+                entry_count,
+                entry_types: entry.values.iter().map(|value| value.r#type).collect(),
+                entry_values,
+            });
         }
+
+        let mut file = self.write_sections(&entries, &value_strings, &checksum_entries, &checksum_strings);
+
+        // The values have a fixed length, so writing the size doesn't change it
+        if let Some(size_entry) = entries.first_mut().filter(|entry| self.opaque.size_entry && entry.entry_types.first() == Some(&T2bValueType::Integer)) {
+            size_entry.entry_values[0] = encode_value(&size_value(file.len(), self.value_length), self.value_length, self.encoding, &mut value_strings)
+                .ok_or_else(|| invalid_data(format!("The size of the file, {:#X}, does not fit in entry {}", file.len(), self.entries[0].name)))?;
+            file = self.write_sections(&entries, &value_strings, &checksum_entries, &checksum_strings);
+        }
+
+        Ok(file)
+    }
+
+    fn write_sections(&self, entries: &[entry_section::T2bEntry], value_strings: &StringPool, checksum_entries: &[T2bChecksumEntry], checksum_strings: &StringPool) -> Vec<u8> {
+        let mut binary_writer = BinaryWriter::new();
+
+        T2bEntrySection::write(&mut binary_writer, entries, self.value_length, value_strings, self.opaque.string_data_count);
+        binary_writer.seek_alignment(0x10);
+
+        T2bChecksumSection::write(&mut binary_writer, checksum_entries, checksum_strings);
+
+        T2bFooter::new(self.opaque.footer_unk1, self.encoding.into(), self.opaque.footer_unk2).write(&mut binary_writer);
+
+        binary_writer.into_inner()
     }
 }
 
+/// The value of the leading entry of a file of `size` bytes
+fn size_value(size: usize, value_length: ValueLength) -> T2bEntryValue {
+    match value_length {
+        ValueLength::Int => T2bValue::Integer(size as i32),
+        ValueLength::Long => T2bValue::Long(size as i64),
+    }.into()
+}
+
+/// Encodes the value the way it is stored in the entry section. String values are added to the value string pool.
+fn encode_value(value: &T2bEntryValue, value_length: ValueLength, encoding: T2bEncoding, strings: &mut StringPool) -> Option<i64> {
+    let encoded = match (value.r#type, &value.value) {
+        (T2bValueType::String, T2bValue::String(v)) => {
+            if v.is_empty() {
+                -1
+            } else {
+                strings.add(&encoding.encode(v)?) as i64
+            }
+        },
+        (T2bValueType::Integer, T2bValue::Integer(v)) => *v as i64,
+        (T2bValueType::Integer, T2bValue::Long(v)) => match value_length {
+            ValueLength::Int => i32::try_from(*v).ok()? as i64,
+            ValueLength::Long => *v,
+        },
+        (T2bValueType::FloatingPoint, T2bValue::F32(v)) => match value_length {
+            ValueLength::Int => v.to_bits() as i64,
+            ValueLength::Long => (*v as f64).to_bits() as i64,
+        },
+        (T2bValueType::FloatingPoint, T2bValue::F64(v)) => match value_length {
+            ValueLength::Int => (*v as f32).to_bits() as i64,
+            ValueLength::Long => v.to_bits() as i64,
+        },
+        _ => return None,
+    };
+
+    Some(encoded)
+}
+
+/// The algorithm of the checksums of the entry names
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum HashType {
     Crc32Standard,
     Crc32Jam,
 }
 
-fn try_detect_hash_type(entry: &T2bChecksumEntry, string_data: &[u8], encoding: i16) -> Option<HashType> {
-    let string_value = read_string(string_data, entry.string_offset, encoding).unwrap();
+impl HashType {
+    /// Computes the checksum of the encoded bytes of a name
+    pub fn compute(self, data: &[u8]) -> u32 {
+        match self {
+            HashType::Crc32Standard => compute_crc32_standard(data),
+            HashType::Crc32Jam => compute_crc32_jam(data),
+        }
+    }
+}
+
+/// The checksums are computed on the encoded names, so the hash type is detected on the raw string bytes
+fn try_detect_hash_type(entry: &T2bChecksumEntry, string_data: &[u8], strings_position: usize) -> Option<HashType> {
+    let string_value = read_string_bytes(string_data, entry.string_offset as i64, strings_position, "T2B checksum strings").ok()?;
 
     let hash_types = [HashType::Crc32Standard, HashType::Crc32Jam];
     
     for hash_type in hash_types {
-        let string_hash = match hash_type {
-            HashType::Crc32Standard => compute_crc32_standard(string_value.as_bytes()),
-            HashType::Crc32Jam => compute_crc32_jam(string_value.as_bytes()),
-        };
-
-        if string_hash == entry.crc {
+        if hash_type.compute(string_value) == entry.crc {
             return Some(hash_type)
         }
     }
     None
 }
 
-fn read_string(string_data: &[u8], offset: u32, _encoding: i16) -> Option<String> {
-    let mut end_offset = offset;
-    while string_data[end_offset as usize] != 0 {
-        end_offset += 1;
-    }
+/// Reads the null-terminated string at `offset` in the string data, which starts at `position` in the file
+fn read_string(string_data: &[u8], offset: i64, encoding: T2bEncoding, position: usize, section: &'static str) -> Result<String, CfgBinError> {
+    let bytes = read_string_bytes(string_data, offset, position, section)?;
 
-    String::from_utf8(string_data[offset as usize..end_offset as usize].to_vec()).ok()
+    encoding.decode(bytes).ok_or(CfgBinError::InvalidString { offset: position + offset as usize, section, encoding: encoding.name() })
 }
 
-fn compute_crc32_standard(data: &[u8]) -> u32 {
-    let params = CrcParams::new(
-        "CRC/STANDARD", 
-        32, 
-        0x04C11DB7, 
-        0xFFFF_FFFF, 
-        true, 
-        0xFFFF_FFFF, 
-        0
-    );
-
-    checksum_with_params(params, data) as u32
-}
+/// Returns the bytes of the null-terminated string at `offset` in the string data, without the terminator
+fn read_string_bytes<'a>(string_data: &'a [u8], offset: i64, position: usize, section: &'static str) -> Result<&'a [u8], CfgBinError> {
+    let out_of_range = CfgBinError::StringOffsetOutOfRange { offset: position, section, string_offset: offset };
 
-fn compute_crc32_jam(data: &[u8]) -> u32 {
-    let params = CrcParams::new(
-        "CRC/JAM", 
-        32, 
-        0x04C11DB7, 
-        0xFFFF_FFFF, 
-        true, 
-        0x0000_0000, 
-        0
-    );
-
-    checksum_with_params(params, data) as u32
+    let start = usize::try_from(offset).map_err(|_| out_of_range.clone())?;
+    let bytes = string_data.get(start..).ok_or(out_of_range.clone())?;
+    let length = bytes.iter().position(|byte| *byte == 0).ok_or(out_of_range)?;
+
+    Ok(&bytes[..length])
 }
 
+/// A named entry and its values
+#[derive(Debug, Clone, PartialEq)]
 pub struct T2bEntry {
     pub name: String,
     pub values: Vec<T2bEntryValue>,
 }
 
-#[derive(Debug)]
+/// A value of an entry, with the type stored in the entry header
+#[derive(Debug, Clone, PartialEq)]
 pub struct T2bEntryValue {
     pub r#type: T2bValueType,
     pub value: T2bValue,
 }
 
-#[derive(Debug)]
+impl From<T2bValue> for T2bEntryValue {
+    /// Pairs the value with the type it is stored as
+    fn from(value: T2bValue) -> Self {
+        let r#type = match value {
+            T2bValue::String(_) => T2bValueType::String,
+            T2bValue::Integer(_) | T2bValue::Long(_) => T2bValueType::Integer,
+            T2bValue::F32(_) | T2bValue::F64(_) => T2bValueType::FloatingPoint,
+        };
+
+        T2bEntryValue { r#type, value }
+    }
+}
+
+/// The value itself. `Long` and `F64` need a file with 8-byte values.
+#[derive(Debug, Clone, PartialEq)]
 pub enum T2bValue {
     String(String),
     Integer(i32),
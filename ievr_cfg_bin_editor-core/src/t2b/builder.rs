@@ -0,0 +1,97 @@
+use std::io;
+
+use crate::common::{invalid_data, string_pool::StringPool};
+
+use super::{encode_value, size_value, HashType, T2b, T2bBlock, T2bEncoding, T2bEntry, T2bEntryValue, T2bNode, T2bOpaqueFields, T2bTree, T2bValue, ValueLength};
+
+/// Builds a T2B file from named entries and blocks.
+///
+/// The file starts with an entry that holds its size, which is computed when the file is built.
+/// The checksums of the names and the footer are written by [`T2b::to_bytes`] from the hash type and encoding.
+#[derive(Debug, Clone)]
+pub struct T2bBuilder {
+    size_entry: String,
+    nodes: Vec<T2bNode>,
+    encoding: T2bEncoding,
+    value_length: ValueLength,
+    hash_type: HashType,
+}
+
+impl T2bBuilder {
+    /// Starts a file whose leading entry, named `size_entry`, holds the size of the file.
+    /// Integers that don't fit in 4 bytes need [`ValueLength::Long`], and the names and strings must be valid in `encoding`.
+    pub fn new(size_entry: &str, encoding: T2bEncoding, value_length: ValueLength, hash_type: HashType) -> T2bBuilder {
+        T2bBuilder {
+            size_entry: size_entry.to_string(),
+            nodes: Vec::new(),
+            encoding,
+            value_length,
+            hash_type,
+        }
+    }
+
+    /// Adds an entry after the previous ones. The value types follow the values.
+    pub fn add_entry(&mut self, name: &str, values: Vec<T2bValue>) -> io::Result<()> {
+        let entry = T2bEntry {
+            name: name.to_string(),
+            values: values.into_iter().map(T2bEntryValue::from).collect(),
+        };
+
+        self.check_entry(&entry)?;
+        self.nodes.push(T2bNode::Entry(entry));
+        Ok(())
+    }
+
+    /// Adds a block and its children after the previous entries, see [`T2bBlock::new`]
+    pub fn add_block(&mut self, block: T2bBlock) -> io::Result<()> {
+        let entries = T2bTree { nodes: vec![T2bNode::Block(block.clone())] }.into_entries();
+        for entry in &entries {
+            self.check_entry(entry)?;
+        }
+
+        self.nodes.push(T2bNode::Block(block));
+        Ok(())
+    }
+
+    pub fn build(self) -> io::Result<T2b> {
+        let size_entry = T2bEntry { name: self.size_entry.clone(), values: vec![size_value(0, self.value_length)] };
+        self.check_entry(&size_entry)?;
+
+        let mut entries = vec![size_entry];
+        entries.extend(T2bTree { nodes: self.nodes }.into_entries());
+
+        let mut t2b = T2b {
+            entries,
+            encoding: self.encoding,
+            value_length: self.value_length,
+            hash_type: self.hash_type,
+            opaque: T2bOpaqueFields::default(),
+            dictionary_names: Default::default(),
+        };
+
+        let size = t2b.to_bytes()?.len();
+        t2b.entries[0].values[0] = size_value(size, self.value_length);
+
+        Ok(t2b)
+    }
+
+    /// Checks that the writer can store the entry in this file
+    fn check_entry(&self, entry: &T2bEntry) -> io::Result<()> {
+        if entry.name.is_empty() {
+            return Err(invalid_data("An entry needs a name".to_string()))
+        }
+        if self.encoding.encode(&entry.name).is_none() {
+            return Err(invalid_data(format!("Entry name {} cannot be encoded in {}", entry.name, self.encoding.name())))
+        }
+        if entry.values.len() > u8::MAX as usize {
+            return Err(invalid_data(format!("Entry {} has {} values, but at most {} are supported", entry.name, entry.values.len(), u8::MAX)))
+        }
+
+        let mut strings = StringPool::new();
+        if let Some(value) = entry.values.iter().find(|value| encode_value(value, self.value_length, self.encoding, &mut strings).is_none()) {
+            return Err(invalid_data(format!("Entry {} cannot store {:?} as {:?} in {}", entry.name, value.value, value.r#type, self.encoding.name())))
+        }
+
+        Ok(())
+    }
+}
\ No newline at end of file
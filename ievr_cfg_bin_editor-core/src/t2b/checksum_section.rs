@@ -1,4 +1,4 @@
-use crate::common::binary_reader::BinaryReader;
+use crate::{error::CfgBinError, common::{binary_reader::BinaryReader, binary_writer::BinaryWriter, string_pool::StringPool}};
 
 pub struct T2bChecksumSection {
     pub(crate) checksum_entries: Vec<T2bChecksumEntry>,
@@ -7,25 +7,58 @@ pub struct T2bChecksumSection {
 }
 
 impl T2bChecksumSection {
-    pub fn read(binary_reader: &mut BinaryReader) -> Option<T2bChecksumSection> {
+    pub fn read(binary_reader: &mut BinaryReader) -> Result<T2bChecksumSection, CfgBinError> {
         let section_position = binary_reader.position();
+        let section = "T2B checksum section";
 
-        let checksum_header = T2bChecksumHeader::read(binary_reader);
+        binary_reader.set_section(section);
+        let checksum_header = T2bChecksumHeader::read(binary_reader)?;
 
         let string_offset = section_position as u64 + checksum_header.string_offset as u64;
 
-        let checksum_entries = if checksum_header.count > 0 {
-            read_checksum_entries(binary_reader, checksum_header.count)
-        } else { return None };
+        binary_reader.ensure_available(string_offset as usize, checksum_header.string_size as usize, "T2B checksum strings")?;
 
-        Some(T2bChecksumSection {
+        let mut entry_reader = binary_reader.window(binary_reader.position(), checksum_header.count as usize * 8, section)?;
+        let checksum_entries = read_checksum_entries(&mut entry_reader, checksum_header.count)?;
+
+        Ok(T2bChecksumSection {
             checksum_entries,
             string_offset,
             string_size: checksum_header.string_size as i32,
         })
     }
+
+    /// Writes the checksum entries followed by their name strings, which start on the next 0x10 boundary
+    pub fn write(binary_writer: &mut BinaryWriter, checksum_entries: &[T2bChecksumEntry], strings: &StringPool) {
+        let section_position = binary_writer.position();
+        binary_writer.skip(CHECKSUM_HEADER_SIZE);
+
+        for entry in checksum_entries {
+            binary_writer.write_u32(entry.crc);
+            binary_writer.write_u32(entry.string_offset);
+        }
+
+        binary_writer.seek_alignment(0x10);
+        let string_offset = binary_writer.position() - section_position;
+
+        binary_writer.write_bytes(strings.data());
+        binary_writer.seek_alignment(0x10);
+        let end_position = binary_writer.position();
+
+        binary_writer.set_position(section_position);
+        T2bChecksumHeader {
+            _size: (end_position - section_position) as u32,
+            count: checksum_entries.len() as u32,
+            string_offset: string_offset as u32,
+            string_size: strings.data().len() as u32,
+        }.write(binary_writer);
+
+        binary_writer.set_position(end_position);
+    }
 }
 
+const CHECKSUM_HEADER_SIZE: usize = 0x10;
+
 pub struct T2bChecksumHeader {
     _size: u32,
     count: u32,
@@ -34,30 +67,40 @@ pub struct T2bChecksumHeader {
 }
 
 impl T2bChecksumHeader {
-    fn read(binary_reader: &mut BinaryReader) -> T2bChecksumHeader {
-        T2bChecksumHeader {
-            _size: binary_reader.read_u32(),
-            count: binary_reader.read_u32(),
-            string_offset: binary_reader.read_u32(),
-            string_size: binary_reader.read_u32(),
-        }
+    fn read(binary_reader: &mut BinaryReader) -> Result<T2bChecksumHeader, CfgBinError> {
+        Ok(T2bChecksumHeader {
+            _size: binary_reader.read_u32()?,
+            count: binary_reader.read_u32()?,
+            string_offset: binary_reader.read_u32()?,
+            string_size: binary_reader.read_u32()?,
+        })
+    }
+
+    fn write(&self, binary_writer: &mut BinaryWriter) {
+        binary_writer.write_u32(self._size);
+        binary_writer.write_u32(self.count);
+        binary_writer.write_u32(self.string_offset);
+        binary_writer.write_u32(self.string_size);
     }
 }
 
 pub struct T2bChecksumEntry {
+    /// Where the entry starts in the file
+    pub(crate) position: usize,
     pub(crate) crc: u32,
     pub(crate) string_offset: u32,
 }
 
-fn read_checksum_entries(binary_reader: &mut BinaryReader, count: u32) -> Vec<T2bChecksumEntry> {
+fn read_checksum_entries(binary_reader: &mut BinaryReader, count: u32) -> Result<Vec<T2bChecksumEntry>, CfgBinError> {
     let mut result = Vec::with_capacity(count as usize);
     for _ in 0..count {
         result.push(
             T2bChecksumEntry {
-                crc: binary_reader.read_u32(),
-                string_offset: binary_reader.read_u32(),
+                position: binary_reader.file_position(),
+                crc: binary_reader.read_u32()?,
+                string_offset: binary_reader.read_u32()?,
             }
         );
     };
-    result
+    Ok(result)
 }
\ No newline at end of file
@@ -0,0 +1,64 @@
+use encoding_rs::{Encoding, SHIFT_JIS, UTF_8};
+use serde::{Deserialize, Serialize};
+
+/// The string encoding declared in the T2B footer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum T2bEncoding {
+    ShiftJis,
+    #[default]
+    Utf8,
+    /// A footer value with no known meaning. The strings are handled as UTF-8 and the value is written back unchanged.
+    Unknown(i16),
+}
+
+impl T2bEncoding {
+    fn encoding(self) -> &'static Encoding {
+        match self {
+            T2bEncoding::ShiftJis => SHIFT_JIS,
+            T2bEncoding::Utf8 | T2bEncoding::Unknown(_) => UTF_8,
+        }
+    }
+
+    /// The name of the encoding used for the strings
+    pub fn name(self) -> &'static str {
+        self.encoding().name()
+    }
+
+    /// Decodes the bytes of a string, or returns `None` if they are not valid in this encoding
+    pub fn decode(self, bytes: &[u8]) -> Option<String> {
+        self.encoding()
+            .decode_without_bom_handling_and_without_replacement(bytes)
+            .map(|string| string.into_owned())
+    }
+
+    /// Encodes a string, or returns `None` if it has characters this encoding cannot represent
+    pub fn encode(self, string: &str) -> Option<Vec<u8>> {
+        let (bytes, _, had_errors) = self.encoding().encode(string);
+
+        if had_errors {
+            None
+        } else {
+            Some(bytes.into_owned())
+        }
+    }
+}
+
+impl From<i16> for T2bEncoding {
+    fn from(value: i16) -> Self {
+        match value {
+            0 => T2bEncoding::ShiftJis,
+            1 => T2bEncoding::Utf8,
+            _ => T2bEncoding::Unknown(value),
+        }
+    }
+}
+
+impl From<T2bEncoding> for i16 {
+    fn from(value: T2bEncoding) -> Self {
+        match value {
+            T2bEncoding::ShiftJis => 0,
+            T2bEncoding::Utf8 => 1,
+            T2bEncoding::Unknown(value) => value,
+        }
+    }
+}
\ No newline at end of file
@@ -1,130 +1,165 @@
 use serde::{Deserialize, Serialize};
 
-use crate::common::binary_reader::BinaryReader;
+use crate::{error::CfgBinError, common::{binary_reader::BinaryReader, binary_writer::BinaryWriter, string_pool::StringPool}};
 
 #[derive(Debug)]
 pub struct T2bEntrySection {
     pub(crate) entries: Vec<T2bEntry>,
     pub(crate) string_offset: i64,
     pub(crate) string_size: i32,
+    pub(crate) string_count: u32,
     pub(crate) value_length: ValueLength,
 }
 
 impl T2bEntrySection {
-    pub fn read(binary_reader: &mut BinaryReader) -> Option<T2bEntrySection> {
+    pub fn read(binary_reader: &mut BinaryReader) -> Result<T2bEntrySection, CfgBinError> {
         let section_position = binary_reader.position();
+        let section = "T2B entry section";
 
-        let entry_header = T2bEntryHeader::read(binary_reader);
+        binary_reader.set_section(section);
+        let entry_header = T2bEntryHeader::read(binary_reader)?;
         let string_offset = (section_position + entry_header.string_data_offset as usize) as i64;
 
+        binary_reader.ensure_available(string_offset as usize, entry_header.string_data_length as usize, "T2B value strings")?;
+
+        // The entries fill the space between the header and the value strings
+        let entries_position = binary_reader.position();
+        let entries_size = (string_offset as usize).checked_sub(entries_position)
+            .ok_or(CfgBinError::InvalidOffset { offset: section_position + 4, section, value: entry_header.string_data_offset as i64 })?;
+        let mut entries_reader = binary_reader.window(entries_position, entries_size, section)?;
+
         let mut value_length = ValueLength::Int;
         let mut entries = Vec::new();
 
         if entry_header.entry_count > 0 {
-            match try_detect_value_length(binary_reader, entry_header.entry_count, string_offset as u64) {
+            match try_detect_value_length(&entries_reader, entry_header.entry_count) {
                 Some(length) => { 
-                    entries = read_entries(binary_reader, entry_header.entry_count, length);
+                    entries = read_entries(&mut entries_reader, entry_header.entry_count, length)?;
                     value_length = length;
                 }
-                None => return None,
+                None => return Err(CfgBinError::UndetectableValueLength { offset: section_position, section }),
             }
         }
 
-        Some(T2bEntrySection {
+        // The entries were read through a window, the section ends with the value strings
+        binary_reader.set_position(entries_position + entries_size)?;
+
+        Ok(T2bEntrySection {
             entries,
             string_offset,
             string_size: entry_header.string_data_length as i32,
+            string_count: entry_header.string_data_count,
             value_length,
         })
     }
+
+    /// Writes the entries followed by the value string data, which starts on the next 0x10 boundary.
+    /// The string count is taken from the pool unless one is given.
+    pub fn write(binary_writer: &mut BinaryWriter, entries: &[T2bEntry], value_length: ValueLength, strings: &StringPool, string_count: Option<u32>) {
+        let section_position = binary_writer.position();
+        binary_writer.skip(ENTRY_HEADER_SIZE);
+
+        for entry in entries {
+            write_entry(binary_writer, entry, value_length);
+        }
+
+        binary_writer.seek_alignment(0x10);
+        let string_data_offset = binary_writer.position() - section_position;
+
+        binary_writer.write_bytes(strings.data());
+        let end_position = binary_writer.position();
+
+        binary_writer.set_position(section_position);
+        T2bEntryHeader {
+            entry_count: entries.len() as u32,
+            string_data_offset: string_data_offset as u32,
+            string_data_length: strings.data().len() as u32,
+            string_data_count: string_count.unwrap_or(strings.count() as u32),
+        }.write(binary_writer);
+
+        binary_writer.set_position(end_position);
+    }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// The size in bytes of every value of the entries
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum ValueLength {
     Int = 4,
     Long = 8
 }
 
+const ENTRY_HEADER_SIZE: usize = 0x10;
+
 pub struct T2bEntryHeader {
     entry_count: u32,
     string_data_offset: u32,
     string_data_length: u32,
-    _string_data_count: u32,
+    string_data_count: u32,
 }
 
 impl T2bEntryHeader {
-    pub fn read(binary_reader: &mut BinaryReader) -> T2bEntryHeader {
-        T2bEntryHeader {
-            entry_count: binary_reader.read_u32(),
-            string_data_offset: binary_reader.read_u32(),
-            string_data_length: binary_reader.read_u32(),
-            _string_data_count: binary_reader.read_u32(),
-        }
+    pub fn read(binary_reader: &mut BinaryReader) -> Result<T2bEntryHeader, CfgBinError> {
+        Ok(T2bEntryHeader {
+            entry_count: binary_reader.read_u32()?,
+            string_data_offset: binary_reader.read_u32()?,
+            string_data_length: binary_reader.read_u32()?,
+            string_data_count: binary_reader.read_u32()?,
+        })
     }
-}
 
-fn try_detect_value_length(binary_reader: &mut BinaryReader, entry_count: u32, data_end_offset: u64) -> Option<ValueLength> {
-    let original_pos = binary_reader.position();
+    pub fn write(&self, binary_writer: &mut BinaryWriter) {
+        binary_writer.write_u32(self.entry_count);
+        binary_writer.write_u32(self.string_data_offset);
+        binary_writer.write_u32(self.string_data_length);
+        binary_writer.write_u32(self.string_data_count);
+    }
+}
 
+/// Tries both value lengths on the entries, which must fill `entries_reader` up to the alignment padding
+fn try_detect_value_length(entries_reader: &BinaryReader, entry_count: u32) -> Option<ValueLength> {
     let value_lengths = [ValueLength::Int, ValueLength::Long];
 
-    for length in value_lengths {
-        if try_read_entry_section(binary_reader, entry_count, data_end_offset, length as i32) {
-            binary_reader.set_position(original_pos);
-            return Some(length)
-        }
-        binary_reader.set_position(original_pos);
-    }
-
-    return None
+    value_lengths.into_iter()
+        .find(|length| matches!(try_read_entry_section(entries_reader.clone(), entry_count, *length as usize), Ok(true)))
 }
 
-fn try_read_entry_section(binary_reader: &mut BinaryReader, entry_count: u32, data_end_offset: u64, length: i32) -> bool {
-    let value_lengths = [length, length, length];
-
+fn try_read_entry_section(mut binary_reader: BinaryReader, entry_count: u32, length: usize) -> Result<bool, CfgBinError> {
     for _ in 0..entry_count {
-        if binary_reader.position() + 8 > data_end_offset as usize {
-            return false;
-        }
+        binary_reader.skip(4)?;
 
-        binary_reader.skip(4);
+        let count = binary_reader.read_byte()? as i32;
+        let types = read_entry_types(&mut binary_reader, count)?;
 
-        let count = binary_reader.read_byte() as i32;
-        let types = read_entry_types(binary_reader, count);
-
-        if binary_reader.position() as u64 > data_end_offset {
-            return false
-        }
-
-        if types.iter().any(|t| *t == T2bValueType::Invalid) {
-            return false
+        if types.contains(&T2bValueType::Invalid) {
+            return Ok(false)
         }
 
-        binary_reader.skip(types.iter().map(|t| value_lengths[(*t as u8) as usize] as usize).sum());
+        binary_reader.skip(types.len() * length)?;
     }
 
-    return binary_reader.position() as u64 <= data_end_offset && (data_end_offset - binary_reader.position() as u64) < 0x10;
+    Ok(binary_reader.remaining_bytes().len() < 0x10)
 }
 
-fn read_entry_types(binary_reader: &mut BinaryReader, count: i32) -> Vec<T2bValueType> {
+fn read_entry_types(binary_reader: &mut BinaryReader, count: i32) -> Result<Vec<T2bValueType>, CfgBinError> {
     let mut types = Vec::with_capacity(count as usize);
 
     for j in (0..count).step_by(4) {
-        let type_chunk = binary_reader.read_byte();
+        let type_chunk = binary_reader.read_byte()?;
         for h in 0..4 {
             if j + h >= count {
                 break
             }
 
-            types.push(T2bValueType::from((type_chunk >> h * 2) & 0x3));
+            types.push(T2bValueType::from((type_chunk >> (h * 2)) & 0x3));
         }
     }
 
-    binary_reader.seek_alignment(4);
+    binary_reader.seek_alignment(4)?;
 
-    return types
+    Ok(types)
 }
 
+/// The 2-bit type of a value in the entry header
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum T2bValueType {
@@ -147,22 +182,26 @@ impl From<u8> for T2bValueType {
 
 #[derive(Debug)]
 pub struct T2bEntry {
+    /// Where the entry starts in the file
+    pub(crate) position: usize,
     pub(crate) crc32: u32,
     pub(crate) entry_count: u8,
     pub(crate) entry_types: Vec<T2bValueType>,
     pub(crate) entry_values: Vec<i64>,
 }
 
-fn read_entries(binary_reader: &mut BinaryReader, entry_count: u32, value_length: ValueLength) -> Vec<T2bEntry> {
+fn read_entries(binary_reader: &mut BinaryReader, entry_count: u32, value_length: ValueLength) -> Result<Vec<T2bEntry>, CfgBinError> {
     let mut result = Vec::with_capacity(entry_count as usize);
 
     for _ in 0..entry_count {
-        let crc32 = binary_reader.read_u32();
-        let entry_count = binary_reader.read_byte();
-        let entry_types = read_entry_types(binary_reader, entry_count as i32);
-        let entry_values = read_entry_values(binary_reader, &entry_types, value_length);
+        let position = binary_reader.file_position();
+        let crc32 = binary_reader.read_u32()?;
+        let entry_count = binary_reader.read_byte()?;
+        let entry_types = read_entry_types(binary_reader, entry_count as i32)?;
+        let entry_values = read_entry_values(binary_reader, &entry_types, value_length)?;
 
         result.push(T2bEntry {
+            position,
             crc32,
             entry_count,
             entry_types,
@@ -170,18 +209,43 @@ fn read_entries(binary_reader: &mut BinaryReader, entry_count: u32, value_length
         });
     }
 
-    result
+    Ok(result)
 }
 
-fn read_entry_values(binary_reader: &mut BinaryReader, types: &Vec<T2bValueType>, value_length: ValueLength) -> Vec<i64> {
+fn read_entry_values(binary_reader: &mut BinaryReader, types: &[T2bValueType], value_length: ValueLength) -> Result<Vec<i64>, CfgBinError> {
     let mut values = vec![0i64; types.len()];
 
-    for j in 0..types.len() {
+    for value in values.iter_mut() {
         match value_length {
-            ValueLength::Int => values[j] = binary_reader.read_i32() as i64,
-            ValueLength::Long => values[j] = binary_reader.read_i64(),
+            ValueLength::Int => *value = binary_reader.read_i32()? as i64,
+            ValueLength::Long => *value = binary_reader.read_i64()?,
         }
     }
 
-    values
+    Ok(values)
+}
+
+fn write_entry(binary_writer: &mut BinaryWriter, entry: &T2bEntry, value_length: ValueLength) {
+    binary_writer.write_u32(entry.crc32);
+    binary_writer.write_byte(entry.entry_count);
+    write_entry_types(binary_writer, &entry.entry_types);
+
+    for value in &entry.entry_values {
+        match value_length {
+            ValueLength::Int => binary_writer.write_i32(*value as i32),
+            ValueLength::Long => binary_writer.write_i64(*value),
+        }
+    }
+}
+
+fn write_entry_types(binary_writer: &mut BinaryWriter, types: &[T2bValueType]) {
+    for chunk in types.chunks(4) {
+        let type_chunk = chunk.iter()
+            .enumerate()
+            .fold(0u8, |acc, (h, t)| acc | ((*t as u8) << (h * 2)));
+
+        binary_writer.write_byte(type_chunk);
+    }
+
+    binary_writer.seek_alignment(4);
 }
\ No newline at end of file
@@ -1,19 +1,37 @@
-use crate::common::binary_reader::BinaryReader;
+use crate::{error::CfgBinError, common::{binary_reader::BinaryReader, binary_writer::BinaryWriter}};
+
+pub(super) const T2B_MAGIC: u32 = 0x62327401; // .t2b in little-endian
+pub(super) const FOOTER_SIZE: usize = 0x10;
 
 pub struct T2bFooter {
     pub(super) magic: u32,
-    _unk1: i16,
+    pub(super) unk1: i16,
     pub(super) encoding: i16,
-    _unk2: i16,
+    pub(super) unk2: i16,
 }
 
 impl T2bFooter {
-    pub fn read(binary_reader: &mut BinaryReader) -> T2bFooter {
-        let magic = binary_reader.read_u32();
-        let _unk1 = binary_reader.read_i16();
-        let encoding = binary_reader.read_i16();
-        let _unk2 = binary_reader.read_i16();
+    pub fn new(unk1: i16, encoding: i16, unk2: i16) -> T2bFooter {
+        T2bFooter { magic: T2B_MAGIC, unk1, encoding, unk2 }
+    }
+
+    pub fn read(binary_reader: &mut BinaryReader) -> Result<T2bFooter, CfgBinError> {
+        let magic = binary_reader.read_u32()?;
+        let unk1 = binary_reader.read_i16()?;
+        let encoding = binary_reader.read_i16()?;
+        let unk2 = binary_reader.read_i16()?;
+
+        Ok(T2bFooter { magic, unk1, encoding, unk2 })
+    }
+
+    pub fn write(&self, binary_writer: &mut BinaryWriter) {
+        let start = binary_writer.position();
+
+        binary_writer.write_u32(self.magic);
+        binary_writer.write_i16(self.unk1);
+        binary_writer.write_i16(self.encoding);
+        binary_writer.write_i16(self.unk2);
 
-        T2bFooter { magic, _unk1, encoding, _unk2 }
+        binary_writer.set_position(start + FOOTER_SIZE);
     }
 }
\ No newline at end of file
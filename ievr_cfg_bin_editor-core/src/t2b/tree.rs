@@ -0,0 +1,155 @@
+use super::{T2bEntry, T2bEntryValue, T2bValue, T2bValueType};
+
+const BEGIN_SUFFIXES: [&str; 2] = ["_BEGIN", "_BEG"];
+const END_SUFFIX: &str = "_END";
+
+/// The entries of a T2B file as a tree.
+///
+/// The entries between a `<NAME>_BEGIN` (or `<NAME>_BEG`) entry and the matching `<NAME>_END` entry
+/// are the children of a block. Markers without a match are kept as plain entries.
+#[derive(Debug, Clone, Default)]
+pub struct T2bTree {
+    pub nodes: Vec<T2bNode>,
+}
+
+#[derive(Debug, Clone)]
+pub enum T2bNode {
+    Entry(T2bEntry),
+    Block(T2bBlock),
+}
+
+#[derive(Debug, Clone)]
+pub struct T2bBlock {
+    pub begin: T2bEntry,
+    pub children: Vec<T2bNode>,
+    pub end: T2bEntry,
+    /// Whether the first value of the begin entry is the number of children.
+    /// When set, it is updated when the tree is turned back into entries.
+    pub counts_children: bool,
+}
+
+impl T2bBlock {
+    /// Creates a block with `<name>_BEGIN` and `<name>_END` markers, whose begin entry holds the number of children
+    pub fn new(name: &str, children: Vec<T2bNode>) -> T2bBlock {
+        T2bBlock {
+            begin: T2bEntry {
+                name: format!("{name}{}", BEGIN_SUFFIXES[0]),
+                values: vec![T2bEntryValue { r#type: T2bValueType::Integer, value: T2bValue::Integer(children.len() as i32) }],
+            },
+            children,
+            end: T2bEntry { name: format!("{name}{END_SUFFIX}"), values: Vec::new() },
+            counts_children: true,
+        }
+    }
+
+    /// The name of the block, without the marker suffix
+    pub fn name(&self) -> &str {
+        begin_name(&self.begin.name).unwrap_or(&self.begin.name)
+    }
+
+    fn update_count(&mut self) {
+        if !self.counts_children {
+            return
+        }
+
+        match self.begin.values.first_mut().map(|value| &mut value.value) {
+            Some(T2bValue::Integer(count)) => *count = self.children.len() as i32,
+            Some(T2bValue::Long(count)) => *count = self.children.len() as i64,
+            _ => {},
+        }
+    }
+}
+
+impl T2bTree {
+    pub fn from_entries(entries: Vec<T2bEntry>) -> T2bTree {
+        // The blocks being read, from the outermost to the innermost
+        let mut open_blocks: Vec<(T2bEntry, Vec<T2bNode>)> = Vec::new();
+        let mut nodes = Vec::new();
+
+        for entry in entries {
+            if begin_name(&entry.name).is_some() {
+                open_blocks.push((entry, Vec::new()));
+                continue
+            }
+
+            let matching_block = end_name(&entry.name).and_then(|name| {
+                open_blocks.iter().rposition(|(begin, _)| begin_name(&begin.name) == Some(name))
+            });
+
+            let Some(index) = matching_block else {
+                match open_blocks.last_mut() {
+                    Some((_, children)) => children.push(T2bNode::Entry(entry)),
+                    None => nodes.push(T2bNode::Entry(entry)),
+                }
+                continue
+            };
+
+            // The blocks opened inside of this one were never closed, their markers are plain entries
+            while open_blocks.len() > index + 1 {
+                let (begin, children) = open_blocks.pop().unwrap();
+                let parent = &mut open_blocks.last_mut().unwrap().1;
+                parent.push(T2bNode::Entry(begin));
+                parent.extend(children);
+            }
+
+            let (begin, children) = open_blocks.pop().unwrap();
+            let counts_children = stored_count(&begin) == Some(children.len() as i64);
+
+            let block = T2bNode::Block(T2bBlock { begin, children, end: entry, counts_children });
+            match open_blocks.last_mut() {
+                Some((_, children)) => children.push(block),
+                None => nodes.push(block),
+            }
+        }
+
+        // Same for the blocks still open at the end of the file
+        while let Some((begin, children)) = open_blocks.pop() {
+            let parent = match open_blocks.last_mut() {
+                Some((_, parent)) => parent,
+                None => &mut nodes,
+            };
+            parent.push(T2bNode::Entry(begin));
+            parent.extend(children);
+        }
+
+        T2bTree { nodes }
+    }
+
+    /// Flattens the tree back into entries, updating the children count of every block that has one
+    pub fn into_entries(self) -> Vec<T2bEntry> {
+        let mut entries = Vec::new();
+        flatten(self.nodes, &mut entries);
+        entries
+    }
+}
+
+fn flatten(nodes: Vec<T2bNode>, entries: &mut Vec<T2bEntry>) {
+    for node in nodes {
+        match node {
+            T2bNode::Entry(entry) => entries.push(entry),
+            T2bNode::Block(mut block) => {
+                block.update_count();
+                entries.push(block.begin);
+                flatten(block.children, entries);
+                entries.push(block.end);
+            },
+        }
+    }
+}
+
+/// The first value of a begin entry, if it is an integer
+fn stored_count(begin: &T2bEntry) -> Option<i64> {
+    match begin.values.first().map(|value| &value.value) {
+        Some(T2bValue::Integer(count)) => Some(*count as i64),
+        Some(T2bValue::Long(count)) => Some(*count),
+        _ => None,
+    }
+}
+
+fn begin_name(name: &str) -> Option<&str> {
+    BEGIN_SUFFIXES.iter().find_map(|suffix| name.strip_suffix(suffix))
+}
+
+fn end_name(name: &str) -> Option<&str> {
+    name.strip_suffix(END_SUFFIX)
+}
\ No newline at end of file
@@ -0,0 +1,28 @@
+mod common;
+
+use common::{ITEM_INFO, ITEM_PARAM};
+use ievr_cfg_bin_editor_core::{parse_database, CellError, Value};
+
+#[test]
+fn set_checks_the_signature_of_the_row() {
+    let database = parse_database(ITEM_INFO).unwrap();
+
+    let table = database.table("ITEM_INFO").unwrap();
+    let mut row = table.rows()[0].clone();
+    assert_eq!(row.get(table.schema(), ""), Some(&Value::Int(1)));
+
+    row.signature = Some(Vec::new());
+    assert_eq!(row.set(table.schema(), "", Value::Int(2)), Err(CellError::MissingColumn { field: String::new() }));
+}
+
+#[test]
+fn set_checks_the_size_of_composite_values() {
+    let database = parse_database(ITEM_PARAM).unwrap();
+
+    let table = database.table("ITEM_PARAM_LIST").unwrap();
+    let mut row = table.rows()[0].clone();
+
+    assert_eq!(row.set(table.schema(), "status", Value::Bytes(vec![1; 4])), Err(CellError::ValueSize { field: "status".to_string(), expected: 8, found: 4 }));
+    assert_eq!(row.set(table.schema(), "status", Value::Bytes(vec![1; 8])), Ok(()));
+    assert_eq!(row.get(table.schema(), "status"), Some(&Value::Bytes(vec![1; 8])));
+}
\ No newline at end of file
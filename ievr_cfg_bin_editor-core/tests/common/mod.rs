@@ -0,0 +1,37 @@
+//! Files and helpers shared by the integration tests
+#![allow(dead_code)]
+
+use ievr_cfg_bin_editor_core::{Rdbn, RdbnBuilder, RdbnFieldDeclaration, RdbnFieldType, RdbnValue};
+
+/// An RDBN file laid out like the game files, with CRC32/JAM hashes and a hash, a string and a composite field
+pub const ITEM_PARAM: &[u8] = include_bytes!("../fixtures/item_param.cfg.bin");
+/// A UTF-8 T2B file with a leading size entry and a `_BEGIN`/`_END` block
+pub const ITEM_INFO: &[u8] = include_bytes!("../fixtures/item_info.cfg.bin");
+/// An RDBN file with every field category, a type declared twice, a string table with an unused name,
+/// and string fields holding a null string, an offset past the string table and Shift-JIS bytes
+pub const CHARA_PARAM: &[u8] = include_bytes!("../fixtures/chara_param.cfg.bin");
+/// A Shift-JIS T2B file with a `_BEGIN`/`_END` block and an empty string
+pub const CHARA_INFO: &[u8] = include_bytes!("../fixtures/chara_info.cfg.bin");
+
+/// The file with `bytes` written at `offset`
+pub fn patched(file: &[u8], offset: usize, bytes: &[u8]) -> Vec<u8> {
+    let mut file = file.to_vec();
+    file[offset..offset + bytes.len()].copy_from_slice(bytes);
+    file
+}
+
+/// An `ITEM_LIST` of two `ITEM_DATA` rows made with the builder. The second row has a null name.
+pub fn item_data() -> Rdbn {
+    let mut builder = RdbnBuilder::new();
+    let item = builder.add_type("ITEM_DATA", 0x1234, vec![
+        RdbnFieldDeclaration::new("id", RdbnFieldType::Int, 1),
+        RdbnFieldDeclaration::new("name", RdbnFieldType::String, 1),
+        RdbnFieldDeclaration::new("price", RdbnFieldType::Short, 1),
+    ]).unwrap();
+    builder.add_list("ITEM_LIST", item, vec![
+        vec![vec![RdbnValue::Int(1)], vec![RdbnValue::String("Potion".to_string())], vec![RdbnValue::Short(300)]],
+        vec![vec![RdbnValue::Int(2)], vec![RdbnValue::NullString], vec![RdbnValue::Short(20)]],
+    ]).unwrap();
+
+    builder.build()
+}
\ No newline at end of file
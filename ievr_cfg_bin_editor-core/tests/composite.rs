@@ -0,0 +1,95 @@
+mod common;
+
+use common::CHARA_PARAM;
+use ievr_cfg_bin_editor_core::{
+    parse_database, CompositeField, CompositeFieldKind, CompositeLayout, CompositeRegistry, CompositeValue, Rdbn, RdbnFieldType, RdbnValue, Value,
+};
+
+fn layout(fields: &[(&str, usize, CompositeFieldKind)]) -> CompositeLayout {
+    CompositeLayout {
+        field_type: RdbnFieldType::StatusRate,
+        size: 8,
+        fields: fields.iter().map(|(name, offset, kind)| CompositeField { name: name.to_string(), offset: *offset, kind: *kind }).collect(),
+    }
+}
+
+#[test]
+fn decode_splits_and_encodes_back() {
+    let layout = layout(&[("rate", 0, CompositeFieldKind::Float), ("flag", 6, CompositeFieldKind::Bool)]);
+    let bytes = [0, 0, 0x80, 0x3F, 0xAA, 0xBB, 1, 0];
+
+    let mut value = layout.decode(&bytes).unwrap();
+    assert_eq!(value.get("rate"), Some(&Value::Float(1.0)));
+    assert_eq!(value.get("flag"), Some(&Value::Bool(true)));
+
+    *value.get_mut("flag").unwrap() = Value::Bool(false);
+    assert_eq!(value.to_bytes().unwrap(), [0, 0, 0x80, 0x3F, 0xAA, 0xBB, 0, 0]);
+}
+
+#[test]
+fn decode_rejects_sub_fields_outside_of_the_value() {
+    assert!(layout(&[("rate", 0, CompositeFieldKind::Float)]).decode(&[0; 4]).is_none());
+    assert!(layout(&[("rate", 6, CompositeFieldKind::Float)]).decode(&[0; 8]).is_none());
+    assert!(layout(&[("rate", usize::MAX, CompositeFieldKind::Float)]).decode(&[0; 8]).is_none());
+
+    let mut registry = CompositeRegistry::new();
+    assert!(registry.register(layout(&[("rate", 6, CompositeFieldKind::Float)])).is_err());
+    assert!(registry.register(layout(&[("rate", usize::MAX, CompositeFieldKind::Float)])).is_err());
+    assert!(registry.register(layout(&[("rate", 4, CompositeFieldKind::Float)])).is_ok());
+}
+
+#[test]
+fn database_composites_are_decoded_and_written_back() {
+    let mut registry = CompositeRegistry::new();
+    registry.register(layout(&[("rate", 0, CompositeFieldKind::Float), ("flag", 6, CompositeFieldKind::Bool)])).unwrap();
+    let registry = CompositeRegistry::from_json(&registry.to_json()).unwrap();
+
+    let mut database = parse_database(CHARA_PARAM).unwrap();
+    assert_eq!(database.decode_composites(&registry), 5);
+    assert_eq!(database.to_bytes().unwrap(), CHARA_PARAM);
+
+    let table = database.table_mut("CHARA_PARAM_LIST").unwrap();
+    let mut row = table.rows()[0].clone();
+    let Some(Value::Composite(status)) = row.get(table.schema(), "status") else {
+        panic!("{:?}", row.get(table.schema(), "status"))
+    };
+    assert_eq!(status.get("flag"), Some(&Value::Bool(true)));
+
+    let mut status = status.clone();
+    *status.get_mut("rate").unwrap() = Value::Float(2.0);
+    row.set(table.schema(), "status", Value::Composite(status)).unwrap();
+    table.rows_mut()[0] = row;
+
+    let rdbn = Rdbn::read(&database.to_bytes().unwrap()).unwrap();
+    assert_eq!(rdbn.lists[0].values[0][8], [RdbnValue::Bytes(vec![0, 0, 0, 0x40, 5, 6, 7, 8])]);
+}
+
+#[test]
+fn default_layouts_decode_the_composite_types() {
+    let registry = CompositeRegistry::with_default_layouts();
+    for field_type in [RdbnFieldType::AbilityData, RdbnFieldType::EnhanceData, RdbnFieldType::StatusRate] {
+        assert_eq!(registry.layout(field_type, 8).map(|layout| layout.size), Some(8));
+    }
+
+    let mut database = parse_database(CHARA_PARAM).unwrap();
+    assert_eq!(database.decode_composites(&registry), 5);
+    assert_eq!(database.to_bytes().unwrap(), CHARA_PARAM);
+
+    let table = database.table("CHARA_PARAM_LIST").unwrap();
+    let Some(Value::Composite(status)) = table.rows()[0].get(table.schema(), "status") else {
+        panic!("{:?}", table.rows()[0].get(table.schema(), "status"))
+    };
+    assert_eq!(status.get("status"), Some(&Value::Hash(0x0403_0201)));
+    assert_eq!(status.members().iter().map(|member| (member.name(), member.offset())).collect::<Vec<_>>(), [("status", 0), ("rate", 4)]);
+}
+
+#[test]
+fn imported_sub_fields_outside_of_the_value_are_rejected() {
+    let value = layout(&[("rate", 4, CompositeFieldKind::Float)]).decode(&[0; 8]).unwrap();
+    let json = serde_json::to_string(&value).unwrap();
+    assert!(json.contains(r#""offset":4"#));
+
+    let moved: CompositeValue = serde_json::from_str(&json.replace(r#""offset":4"#, r#""offset":100"#)).unwrap();
+    assert!(moved.to_bytes().is_none());
+    assert_eq!(moved.raw(), [0; 8]);
+}
\ No newline at end of file
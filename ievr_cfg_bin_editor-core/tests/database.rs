@@ -0,0 +1,246 @@
+mod common;
+
+use common::{patched, CHARA_PARAM, ITEM_INFO, ITEM_PARAM};
+use ievr_cfg_bin_editor_core::{
+    parse_database, parse_database_with_dictionary, Database, FieldMetadata, FormatMetadata, HashDictionary, HashType, Rdbn, RdbnFieldType,
+    RdbnFieldTypeCategory, RowMismatchKind, SchemaMetadata, StringDecoding, T2b, T2bEncoding, T2bEntryValue, T2bValue, T2bValueType, Value,
+    ValueLength, ValueType,
+};
+
+/// The database read back from its JSON export without the format metadata, like the exports of older versions
+fn without_metadata(database: &Database) -> Database {
+    let mut json: serde_json::Value = serde_json::from_str(&database.serialize()).unwrap();
+    json.as_object_mut().unwrap().remove("metadata");
+
+    Database::from_json(&json.to_string()).unwrap()
+}
+
+#[test]
+fn json_export_is_imported_with_a_template() {
+    let database = parse_database(ITEM_PARAM).unwrap();
+    let imported = Database::from_json(&database.serialize()).unwrap();
+
+    let bytes = imported.to_bytes_with_template(ITEM_PARAM).unwrap();
+    assert_eq!(bytes, database.to_bytes_with_template(ITEM_PARAM).unwrap());
+    assert_eq!(parse_database(&bytes).unwrap().serialize(), database.serialize());
+}
+
+#[test]
+fn json_edits_are_imported() {
+    let json = parse_database(ITEM_PARAM).unwrap().serialize();
+    let mut database = Database::from_json(&json.replace(r#""Ether""#, r#""Hi-Ether""#)).unwrap();
+    database.table_mut("ITEM_PARAM_LIST").unwrap().rows_mut()[0].values[2] = vec![Value::Int(75)];
+
+    let read = parse_database(&database.to_bytes_with_template(ITEM_PARAM).unwrap()).unwrap();
+    let rows = read.table("ITEM_PARAM_LIST").unwrap().rows();
+    assert!(matches!(rows[0].values[2][..], [Value::Int(75)]));
+    assert!(matches!(&rows[1].values[1][..], [Value::String(name)] if name == "Hi-Ether"));
+}
+
+#[test]
+fn non_finite_floats_are_imported_unchanged() {
+    let nan = f32::from_bits(0x7FC0_1234);
+
+    let mut database = parse_database(ITEM_PARAM).unwrap();
+    let table = database.table_mut("ITEM_PARAM_LIST").unwrap();
+    for (row, weight) in table.rows_mut().iter_mut().zip([nan, f32::INFINITY, f32::NEG_INFINITY]) {
+        row.values[4] = vec![Value::Float(weight)];
+    }
+
+    let json = database.serialize();
+    assert!(json.contains(r#""0x7FC01234""#) && json.contains(r#""0x7F800000""#) && json.contains(r#""0xFF800000""#));
+
+    let imported = Database::from_json(&json).unwrap();
+    assert_eq!(imported.to_bytes().unwrap(), database.to_bytes().unwrap());
+    let rows = imported.table("ITEM_PARAM_LIST").unwrap().rows();
+    assert!(matches!(rows[0].values[4][..], [Value::Float(weight)] if weight.to_bits() == nan.to_bits()));
+
+    assert!(Database::from_json(&json.replace("0x7F800000", "0xinf")).is_err());
+}
+
+#[test]
+fn json_without_metadata_is_imported_with_a_template() {
+    for file in [ITEM_PARAM, ITEM_INFO] {
+        let database = parse_database(file).unwrap();
+        let imported = without_metadata(&database);
+        assert_eq!(imported.metadata(), &FormatMetadata::None);
+        assert_eq!(imported.to_bytes_with_template(file).unwrap(), database.to_bytes().unwrap());
+    }
+}
+
+#[test]
+fn types_without_a_list_are_kept_at_their_index() {
+    let mut rdbn = Rdbn::read(CHARA_PARAM).unwrap();
+    let mut unused = rdbn.types[1].clone();
+    unused.unk_hash = 0xABCD;
+    rdbn.types.insert(1, unused.clone());
+    rdbn.lists.iter_mut().filter(|list| list.type_index >= 1).for_each(|list| list.type_index += 1);
+    let file = rdbn.to_bytes().unwrap();
+
+    let database = parse_database(&file).unwrap();
+    let FormatMetadata::Rdbn(metadata) = database.metadata() else {
+        panic!("{:?}", database.metadata())
+    };
+    assert_eq!(metadata.unused_types.iter().collect::<Vec<_>>(), [(&1, &unused)]);
+
+    assert_eq!(database.to_bytes().unwrap(), file);
+    assert_eq!(Database::from_json(&database.serialize()).unwrap().to_bytes().unwrap(), file);
+    assert_eq!(database.to_bytes_with_template(&file).unwrap(), file);
+    assert_eq!(without_metadata(&database).to_bytes_with_template(&file).unwrap(), file);
+}
+
+#[test]
+fn metadata_follows_the_source_file() {
+    let database = parse_database(ITEM_INFO).unwrap();
+    assert_eq!(database.encoding(), Some(T2bEncoding::Utf8));
+    let FormatMetadata::T2b(metadata) = database.metadata() else {
+        panic!("{:?}", database.metadata())
+    };
+    assert_eq!((metadata.value_length, metadata.hash_type), (ValueLength::Int, HashType::Crc32Jam));
+
+    let database = parse_database(ITEM_PARAM).unwrap();
+    assert_eq!(database.encoding(), None);
+    let FormatMetadata::Rdbn(metadata) = database.metadata() else {
+        panic!("{:?}", database.metadata())
+    };
+    assert_eq!(metadata.header.version, 1);
+
+    let schema = database.table("ITEM_PARAM_LIST").unwrap().schema();
+    assert_eq!(schema.metadata(), Some(&SchemaMetadata { unk_hash: 0x5678, size: 32, type_index: Some(0), field_index: Some(0) }));
+    assert_eq!(schema.fields()[4].metadata, Some(FieldMetadata { size: 4, offset: 16, category: RdbnFieldTypeCategory::Primitive }));
+}
+
+#[test]
+fn edited_metadata_is_written() {
+    let mut database = parse_database(ITEM_INFO).unwrap();
+    let FormatMetadata::T2b(metadata) = database.metadata_mut() else {
+        panic!()
+    };
+    metadata.encoding = T2bEncoding::ShiftJis;
+    metadata.hash_type = HashType::Crc32Standard;
+
+    let read = parse_database(&database.to_bytes().unwrap()).unwrap();
+    let FormatMetadata::T2b(metadata) = read.metadata() else {
+        panic!("{:?}", read.metadata())
+    };
+    assert_eq!((metadata.encoding, metadata.hash_type), (T2bEncoding::ShiftJis, HashType::Crc32Standard));
+}
+
+/// The T2B fixture with `ITEM_INFO` entries that hold different values
+fn mixed_rows() -> Vec<u8> {
+    let mut t2b = T2b::read(ITEM_INFO).unwrap();
+    t2b.entries[3].values.truncate(1);
+
+    let mut entry = t2b.entries[2].clone();
+    entry.values[0] = T2bEntryValue { r#type: T2bValueType::FloatingPoint, value: T2bValue::F32(3.0) };
+    entry.values.pop();
+    t2b.entries.insert(4, entry);
+    t2b.entries[1].values[0].value = T2bValue::Integer(3);
+
+    t2b.to_bytes().unwrap()
+}
+
+#[test]
+fn rows_with_different_values_are_kept() {
+    let file = mixed_rows();
+    let database = parse_database(&file).unwrap();
+    let table = database.table("ITEM_INFO").unwrap();
+
+    let fields = table.schema().fields();
+    assert_eq!(fields.iter().map(|field| (field.value_type, field.optional)).collect::<Vec<_>>(), [
+        (ValueType::T2b(T2bValueType::Integer), false),
+        (ValueType::T2b(T2bValueType::String), true),
+        (ValueType::T2b(T2bValueType::FloatingPoint), true),
+    ]);
+    assert_eq!(table.schema().required_columns(), 1);
+    assert_eq!(table.rows()[1].values.len(), 1);
+    assert_eq!(table.rows()[2].signature, Some(vec![ValueType::T2b(T2bValueType::FloatingPoint), ValueType::T2b(T2bValueType::String)]));
+
+    assert!(database.validate().is_empty());
+    assert_eq!(database.to_bytes().unwrap(), file);
+}
+
+#[test]
+fn validate_reports_the_mismatching_rows() {
+    let mut database = parse_database(&mixed_rows()).unwrap();
+    let table = database.table_mut("ITEM_INFO").unwrap();
+    table.rows_mut()[0].values[1] = vec![Value::Int(5)];
+    table.rows_mut()[1].values.clear();
+    table.rows_mut()[2].values[0] = vec![Value::Int(3)];
+
+    let kinds = database.validate().into_iter().map(|mismatch| (mismatch.row, mismatch.kind)).collect::<Vec<_>>();
+    assert_eq!(kinds, [
+        (0, RowMismatchKind::ValueType { column: 1, expected: ValueType::T2b(T2bValueType::String), found: Value::Int(5) }),
+        (1, RowMismatchKind::ColumnCount { min: 1, max: 3, found: 0 }),
+        (2, RowMismatchKind::ValueType { column: 0, expected: ValueType::T2b(T2bValueType::FloatingPoint), found: Value::Int(3) }),
+    ]);
+    assert!(database.to_bytes().is_err());
+}
+
+#[test]
+fn validate_reports_the_values_without_the_size_of_their_field() {
+    let mut database = parse_database(CHARA_PARAM).unwrap();
+    let table = database.table_mut("CHARA_PARAM_LIST").unwrap();
+    table.rows_mut()[1].values[8] = vec![Value::Bytes(vec![0; 4])];
+    table.rows_mut()[2].values[9] = vec![Value::Bytes(vec![0; 8])];
+
+    let kinds = database.validate().into_iter().map(|mismatch| (mismatch.row, mismatch.kind)).collect::<Vec<_>>();
+    assert_eq!(kinds, [
+        (1, RowMismatchKind::ValueSize { column: 8, expected: 8, found: 4 }),
+        (2, RowMismatchKind::ValueSize { column: 9, expected: 4, found: 8 }),
+    ]);
+    assert!(database.to_bytes().is_err());
+}
+
+#[test]
+fn hash_values_are_named_from_the_string_table() {
+    let item_param = HashType::Crc32Jam.compute(b"ITEM_PARAM");
+    let database = parse_database(&patched(ITEM_PARAM, 0x1E0, &item_param.to_le_bytes())).unwrap();
+
+    let rows = database.table("ITEM_PARAM_LIST").unwrap().rows();
+    assert_eq!(rows[0].values[0], [Value::Hash(item_param)]);
+    assert_eq!(database.hash_name(item_param), Some("ITEM_PARAM"));
+    assert_eq!(database.hash_names().len(), 1);
+}
+
+#[test]
+fn hash_values_are_named_from_a_dictionary() {
+    let mut dictionary = HashDictionary::new();
+    dictionary.insert(0x1111_1111, "ITEM_POTION".to_string());
+    dictionary.insert(0x2222_2222, "ITEM_ETHER".to_string());
+    dictionary.insert(0x9999_9999, "UNUSED".to_string());
+
+    let mut database = parse_database(ITEM_PARAM).unwrap();
+    assert_eq!(database.hash_name(0x1111_1111), None);
+    assert_eq!(database.resolve_hashes(&dictionary), 2);
+    assert_eq!(database.resolve_hashes(&dictionary), 0);
+    assert_eq!(database.hash_name(0x2222_2222), Some("ITEM_ETHER"));
+    assert_eq!(database.hash_name(0x9999_9999), None);
+    assert_eq!(database.to_bytes().unwrap(), ITEM_PARAM);
+
+    let database = parse_database_with_dictionary(ITEM_PARAM, StringDecoding::default(), &dictionary).unwrap();
+    assert_eq!(database.hash_name(0x1111_1111), Some("ITEM_POTION"));
+    assert_eq!(database.hash_name(0x9999_9999), None);
+}
+
+#[test]
+fn schema_shows_the_rdbn_layout() {
+    let database = parse_database(ITEM_PARAM).unwrap();
+    let schema = database.table("ITEM_PARAM_LIST").unwrap().schema();
+
+    assert_eq!(schema.name(), "ITEM_PARAM");
+    assert_eq!(schema.metadata(), Some(&SchemaMetadata { unk_hash: 0x5678, size: 32, type_index: Some(0), field_index: Some(0) }));
+    assert_eq!(schema.fields().iter().map(|field| field.name.as_str()).collect::<Vec<_>>(), ["id", "name", "price", "stock", "weight", "status", "usable"]);
+
+    let stock = &schema.fields()[3];
+    assert_eq!((stock.value_type, stock.count), (ValueType::Rdbn(RdbnFieldType::Short), 1));
+    assert_eq!((stock.size(), stock.offset(), stock.category()), (Some(2), Some(12), Some(RdbnFieldTypeCategory::Primitive)));
+
+    let status = &schema.fields()[5];
+    assert_eq!((status.size(), status.offset(), status.category()), (Some(8), Some(20), Some(RdbnFieldTypeCategory::Composite)));
+
+    let database = parse_database(ITEM_INFO).unwrap();
+    let schema = database.table("ITEM_INFO").unwrap().schema();
+    assert_eq!(schema.metadata(), None);
+    assert_eq!((schema.fields()[0].size(), schema.fields()[0].offset(), schema.fields()[0].category()), (None, None, None));
+}
\ No newline at end of file
@@ -0,0 +1,29 @@
+use ievr_cfg_bin_editor_core::HashDictionary;
+
+#[test]
+fn json_keys_are_decimal_unless_prefixed() {
+    let dictionary = HashDictionary::from_json(r#"{ "12345678": "decimal", "0x12345678": "hexadecimal" }"#).unwrap();
+
+    assert_eq!(dictionary.get(12345678), Some("decimal"));
+    assert_eq!(dictionary.get(0x12345678), Some("hexadecimal"));
+    assert!(HashDictionary::from_json(r#"{ "1A2B3C4D": "name" }"#).is_err());
+}
+
+#[test]
+fn text_hashes_are_hexadecimal() {
+    let dictionary = HashDictionary::from_text("12345678=first\n0x9ABC second\n# comment\nthird");
+
+    assert_eq!(dictionary.get(0x12345678), Some("first"));
+    assert_eq!(dictionary.get(0x9ABC), Some("second"));
+    assert_eq!(dictionary.get(0x2432_2064), Some("third"));
+}
+
+#[test]
+fn saved_dictionaries_load_back() {
+    let mut dictionary = HashDictionary::new();
+    dictionary.insert(12345678, "first".to_string());
+    dictionary.insert_name("second");
+
+    assert_eq!(HashDictionary::load(&dictionary.to_json()).unwrap(), dictionary);
+    assert_eq!(HashDictionary::load(&dictionary.to_text()).unwrap(), dictionary);
+}
\ No newline at end of file
@@ -0,0 +1,55 @@
+mod common;
+
+use common::item_data;
+use ievr_cfg_bin_editor_core::{Rdbn, RdbnFieldDeclaration, RdbnFieldType, RdbnValue};
+
+#[test]
+fn fields_are_added_moved_and_removed_with_their_values() {
+    let mut rdbn = item_data();
+
+    rdbn.add_field(0, 2, RdbnFieldDeclaration::new("flag", RdbnFieldType::Bool, 1)).unwrap();
+    rdbn.set_field_count(0, 3, 2).unwrap();
+    rdbn.move_field(0, 0, 3).unwrap();
+    assert_eq!(rdbn.types[0].fields.iter().map(|field| field.name.as_str()).collect::<Vec<_>>(), ["name", "flag", "price", "id"]);
+    assert_eq!(rdbn.lists[0].values[0], [
+        vec![RdbnValue::String("Potion".to_string())],
+        vec![RdbnValue::Bool(false)],
+        vec![RdbnValue::Short(300), RdbnValue::Short(0)],
+        vec![RdbnValue::Int(1)],
+    ]);
+
+    assert_eq!(rdbn.remove_field(0, 1).unwrap().name, "flag");
+    assert!(rdbn.add_field(0, 0, RdbnFieldDeclaration::new("id", RdbnFieldType::Int, 1)).is_err());
+
+    let read = Rdbn::read(&rdbn.to_bytes().unwrap()).unwrap();
+    assert_eq!(read.lists[0].values, rdbn.lists[0].values);
+    assert_eq!(read.lists[0].value_size, 12);
+}
+
+#[test]
+fn retype_converts_the_values() {
+    let mut rdbn = item_data();
+
+    rdbn.retype_field(0, 2, RdbnFieldType::Int, None).unwrap();
+    assert_eq!(rdbn.lists[0].values[0][2], [RdbnValue::Int(300)]);
+    assert_eq!(rdbn.lists[0].value_size, 12);
+
+    rdbn.retype_field(0, 0, RdbnFieldType::Flag, None).unwrap();
+    assert_eq!(rdbn.lists[0].values[1][0], [RdbnValue::Int(2)]);
+
+    let read = Rdbn::read(&rdbn.to_bytes().unwrap()).unwrap();
+    assert_eq!(read.types[0].fields[2].field_type, RdbnFieldType::Int);
+    assert_eq!(read.lists[0].values, rdbn.lists[0].values);
+}
+
+#[test]
+fn retype_rejects_values_that_do_not_fit() {
+    let mut rdbn = item_data();
+    let original = rdbn.clone();
+
+    assert!(rdbn.retype_field(0, 2, RdbnFieldType::Byte, None).is_err());
+    assert!(rdbn.retype_field(0, 2, RdbnFieldType::Float, None).is_err());
+    assert!(rdbn.retype_field(0, 2, RdbnFieldType::StatusRate, None).is_err());
+    assert_eq!(rdbn.types, original.types);
+    assert_eq!(rdbn.lists, original.lists);
+}
\ No newline at end of file
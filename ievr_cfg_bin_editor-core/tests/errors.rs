@@ -0,0 +1,42 @@
+mod common;
+
+use common::{patched, ITEM_INFO, ITEM_PARAM};
+use ievr_cfg_bin_editor_core::{parse_database, CfgBinError};
+
+#[test]
+fn truncated_files_are_rejected() {
+    // The strings are the last section of RDBN files, so a file cut right after one of them still holds a string table
+    let string_offset = 0x40 + i32::from_le_bytes(ITEM_PARAM[0x38..0x3C].try_into().unwrap()) as usize;
+    for length in 0..string_offset {
+        assert!(parse_database(&ITEM_PARAM[..length]).is_err(), "{length}");
+    }
+    for length in 0..ITEM_INFO.len() {
+        assert!(parse_database(&ITEM_INFO[..length]).is_err(), "{length}");
+    }
+
+    assert!(matches!(parse_database(&ITEM_PARAM[..0x20]), Err(CfgBinError::Truncated { offset: 0, section: "RDBN header", size: 0x3C })));
+    assert!(matches!(parse_database(&ITEM_PARAM[..0x200]), Err(CfgBinError::InvalidOffset { offset: 0x38, .. })));
+}
+
+#[test]
+fn rdbn_errors_point_to_the_bad_entry() {
+    assert!(matches!(
+        parse_database(&patched(ITEM_PARAM, 0x48, &[0x7F, 0])),
+        Err(CfgBinError::IndexOutOfRange { offset: 0x48, section: "RDBN type entries", index: 0x86, count: 7 })
+    ));
+    assert!(matches!(
+        parse_database(&patched(ITEM_PARAM, 0x66, &[9, 0])),
+        Err(CfgBinError::UnknownFieldCategory { offset: 0x66, section: "RDBN field entries", category: 9 })
+    ));
+    assert!(matches!(
+        parse_database(&patched(ITEM_PARAM, 0x140, &[9, 0])),
+        Err(CfgBinError::IndexOutOfRange { offset: 0x140, section: "RDBN root entries", index: 9, count: 1 })
+    ));
+}
+
+#[test]
+fn t2b_errors_point_to_the_bad_section() {
+    assert!(matches!(parse_database(&patched(ITEM_INFO, ITEM_INFO.len() - 0x10, &[0; 4])), Err(CfgBinError::UnknownFormat)));
+    assert!(matches!(parse_database(&patched(ITEM_INFO, 0x4, &[0xFF, 0xFF])), Err(CfgBinError::Truncated { section: "T2B value strings", .. })));
+    assert!(matches!(parse_database(&patched(ITEM_INFO, 0x15, &[0xFF])), Err(CfgBinError::UndetectableValueLength { offset: 0, .. })));
+}
\ No newline at end of file
@@ -0,0 +1,116 @@
+//! Round trips on the files of `tests/fixtures`. They are small files assembled by hand with the layout of the game files.
+
+mod common;
+
+use common::{CHARA_INFO, CHARA_PARAM, ITEM_INFO, ITEM_PARAM};
+use ievr_cfg_bin_editor_core::{
+    parse_database, CfgBinError, Database, HashType, Rdbn, RdbnFieldType, RdbnValue, StringDecoding, T2b, T2bEncoding, T2bValue, ValueLength,
+};
+
+#[test]
+fn item_param_is_written_back_unchanged() {
+    let database = parse_database(ITEM_PARAM).unwrap();
+    assert_eq!(database.to_bytes().unwrap(), ITEM_PARAM);
+    assert_eq!(Database::from_json(&database.serialize()).unwrap().to_bytes().unwrap(), ITEM_PARAM);
+}
+
+#[test]
+fn item_info_is_written_back_unchanged() {
+    let database = parse_database(ITEM_INFO).unwrap();
+    assert_eq!(database.to_bytes().unwrap(), ITEM_INFO);
+    assert_eq!(Database::from_json(&database.serialize()).unwrap().to_bytes().unwrap(), ITEM_INFO);
+}
+
+fn crc32_jam(name: &str) -> u32 {
+    HashType::Crc32Jam.compute(name.as_bytes())
+}
+
+#[test]
+fn chara_param_is_read() {
+    let rdbn = Rdbn::read(CHARA_PARAM).unwrap();
+
+    assert_eq!(rdbn.types.len(), 3);
+    assert_eq!(rdbn.distinct_types().len(), 2);
+    assert_eq!(rdbn.types[2].field_index, rdbn.types[1].field_index);
+    assert_eq!(rdbn.types[0].fields[8].field_type, RdbnFieldType::StatusRate);
+    // Type 7 holds 8 bytes, so the field of 4 bytes is kept as raw bytes
+    assert_eq!(rdbn.types[0].fields[9].field_type, RdbnFieldType::Unknown(7));
+
+    assert_eq!(rdbn.string_lookup.len(), 19);
+    assert_eq!(rdbn.string_lookup[0].hash, crc32_jam("CHARA_PARAM_LIST"));
+    assert_eq!(rdbn.string(crc32_jam("UNUSED_NAME")), Some("UNUSED_NAME"));
+
+    let chara = &rdbn.lists[0];
+    assert_eq!(chara.name, "CHARA_PARAM_LIST");
+    assert_eq!(chara.values.len(), 5);
+    assert_eq!(chara.values[0], [
+        vec![RdbnValue::Hash(0x1111_1111)],
+        vec![RdbnValue::String("Endou".to_string())],
+        vec![RdbnValue::Byte(99)],
+        vec![RdbnValue::Short(-2)],
+        vec![RdbnValue::Short(120), RdbnValue::Short(-5)],
+        vec![RdbnValue::Float(1.5)],
+        vec![RdbnValue::Float4([1.0, 2.0, -3.5, 0.0])],
+        vec![RdbnValue::Short2([7, -8])],
+        vec![RdbnValue::Bytes((1..=8).collect())],
+        vec![RdbnValue::Bytes(vec![0xAA, 0xBB, 0xCC, 0xDD])],
+        vec![RdbnValue::Bool(true)],
+    ]);
+    assert_eq!(chara.values[1][1], [RdbnValue::String("円堂守".to_string())]);
+    assert_eq!(chara.values[2][1], [RdbnValue::NullString]);
+    assert_eq!(chara.values[3][1], [RdbnValue::StringOffset(0x0012_3456)]);
+    assert_eq!(chara.values[4][1], [RdbnValue::Bytes(vec![0x8D, 0x8B, 0x89, 0x8A, 0x8E, 0x9B])]);
+
+    assert_eq!(rdbn.lists[2].type_index, 2);
+    assert_eq!(rdbn.lists[2].values[0][1], [RdbnValue::String("Potion".to_string())]);
+}
+
+#[test]
+fn chara_param_is_written_back_unchanged() {
+    let rdbn = Rdbn::read(CHARA_PARAM).unwrap();
+    assert_eq!(rdbn.to_bytes().unwrap(), CHARA_PARAM);
+
+    let database = parse_database(CHARA_PARAM).unwrap();
+    assert_eq!(database.to_bytes().unwrap(), CHARA_PARAM);
+    assert_eq!(Database::from_json(&database.serialize()).unwrap().to_bytes().unwrap(), CHARA_PARAM);
+}
+
+#[test]
+fn chara_param_follows_the_decoding() {
+    assert!(matches!(
+        Rdbn::read_with_decoding(CHARA_PARAM, StringDecoding::Strict),
+        Err(CfgBinError::StringOffsetOutOfRange { string_offset: 0x0012_3456, .. })
+    ));
+
+    let rdbn = Rdbn::read_with_decoding(CHARA_PARAM, StringDecoding::Lossy).unwrap();
+    assert!(matches!(&rdbn.lists[0].values[4][1][0], RdbnValue::String(value) if value.contains('\u{FFFD}')));
+}
+
+#[test]
+fn chara_info_is_read() {
+    let t2b = T2b::read(CHARA_INFO).unwrap();
+
+    assert_eq!(t2b.encoding, T2bEncoding::ShiftJis);
+    assert_eq!(t2b.value_length, ValueLength::Int);
+    assert_eq!(t2b.hash_type, HashType::Crc32Jam);
+    assert_eq!(t2b.opaque.string_data_count, None);
+
+    let values = |index: usize| t2b.entries[index].values.iter().map(|value| value.value.clone()).collect::<Vec<_>>();
+    assert_eq!(t2b.entries.len(), 7);
+    assert_eq!(values(0), [T2bValue::Integer(CHARA_INFO.len() as i32)]);
+    assert_eq!(values(2), [T2bValue::Integer(1), T2bValue::String("円堂守".to_string()), T2bValue::F32(1.5)]);
+    assert_eq!(values(3), [T2bValue::Integer(2), T2bValue::String("豪炎寺修也".to_string()), T2bValue::F32(-0.25)]);
+    assert_eq!(values(4)[1], T2bValue::String(String::new()));
+    assert!(values(5).is_empty());
+    assert_eq!(values(6)[4..], [T2bValue::Integer(-5), T2bValue::String("円堂守".to_string())]);
+}
+
+#[test]
+fn chara_info_is_written_back_unchanged() {
+    let t2b = T2b::read(CHARA_INFO).unwrap();
+    assert_eq!(t2b.to_bytes().unwrap(), CHARA_INFO);
+
+    let database = parse_database(CHARA_INFO).unwrap();
+    assert_eq!(database.to_bytes().unwrap(), CHARA_INFO);
+    assert_eq!(Database::from_json(&database.serialize()).unwrap().to_bytes().unwrap(), CHARA_INFO);
+}
\ No newline at end of file
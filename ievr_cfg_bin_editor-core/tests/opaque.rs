@@ -0,0 +1,38 @@
+mod common;
+
+use common::{patched, ITEM_INFO, ITEM_PARAM};
+use ievr_cfg_bin_editor_core::{parse_database, Value};
+
+/// The data size of the RDBN header
+fn data_size(file: &[u8]) -> i32 {
+    i32::from_le_bytes(file[0xC..0x10].try_into().unwrap())
+}
+
+#[test]
+fn rdbn_header_fields_are_kept() {
+    let file = patched(&patched(ITEM_PARAM, 0x6, &[3, 0, 0, 0]), 0xC, &[0x10, 0, 0, 0, 0x42, 0, 0, 0, 0x20]);
+    let bytes = parse_database(&file).unwrap().to_bytes().unwrap();
+
+    assert_eq!(bytes[0x6..0xA], file[0x6..0xA]);
+    assert_eq!(data_size(&bytes), 0x10);
+    assert_eq!(bytes[0x10..0x24], file[0x10..0x24]);
+}
+
+#[test]
+fn rdbn_data_size_follows_the_edits() {
+    let mut database = parse_database(ITEM_PARAM).unwrap();
+    database.table_mut("ITEM_PARAM_LIST").unwrap().rows_mut()[0].values[1] = vec![Value::String("A potion that heals everyone".to_string())];
+
+    let bytes = database.to_bytes().unwrap();
+    assert!(bytes.len() > ITEM_PARAM.len());
+    assert_eq!(data_size(&bytes), bytes.len() as i32 - 0x40);
+}
+
+#[test]
+fn t2b_footer_fields_are_kept() {
+    let footer = ITEM_INFO.len() - 0x10;
+    let file = patched(&patched(ITEM_INFO, footer + 0x4, &[0x23, 0x01]), footer + 0x8, &[0xFF, 0xFF]);
+    let bytes = parse_database(&file).unwrap().to_bytes().unwrap();
+
+    assert_eq!(bytes[bytes.len() - 0x10..], file[footer..]);
+}
\ No newline at end of file
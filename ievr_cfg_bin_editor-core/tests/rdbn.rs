@@ -0,0 +1,248 @@
+mod common;
+
+use std::io::ErrorKind;
+
+use common::{item_data, CHARA_PARAM, ITEM_PARAM};
+use ievr_cfg_bin_editor_core::{
+    parse_database, parse_database_with_dictionary, CfgBinError, Database, FormatMetadata, HashDictionary, HashType, Rdbn, RdbnBuilder,
+    RdbnFieldDeclaration, RdbnFieldType, RdbnString, RdbnValue, StringDecoding, Value,
+};
+
+/// A file whose string table is not in the order of the entries, has a string no entry uses,
+/// and hashes that are not the CRC32 of the names
+fn sample() -> Rdbn {
+    let mut rdbn = item_data();
+    rdbn.string_lookup = [
+        (0xAAAA_0001, "ITEM_LIST"),
+        (0xAAAA_0002, "UNUSED"),
+        (0xAAAA_0003, "ITEM_DATA"),
+        (0xAAAA_0004, "id"),
+        (0xAAAA_0005, "name"),
+        (0xAAAA_0006, "price"),
+    ]
+    .into_iter()
+    .map(|(hash, name)| RdbnString { hash, name: name.to_string(), raw: None })
+    .collect();
+
+    rdbn
+}
+
+#[test]
+fn every_type_id_is_read_and_written() {
+    let types = [
+        (RdbnFieldType::Long, RdbnValue::Long(-1 << 40)),
+        (RdbnFieldType::UInt, RdbnValue::Uint(0xFFFF_FFFE)),
+        (RdbnFieldType::UShort, RdbnValue::UShort(0xFFFE)),
+        (RdbnFieldType::Double, RdbnValue::Double(-0.125)),
+        (RdbnFieldType::Float2, RdbnValue::Float2([1.0, -2.5])),
+        (RdbnFieldType::Float3, RdbnValue::Float3([1.0, 2.0, 3.5])),
+        (RdbnFieldType::Int2, RdbnValue::Int2([-7, 8])),
+    ];
+
+    let mut builder = RdbnBuilder::new();
+    let fields = types.iter().enumerate().map(|(i, (field_type, _))| RdbnFieldDeclaration::new(&format!("field{i}"), *field_type, 1)).collect();
+    let all = builder.add_type("ALL", 0, fields).unwrap();
+    builder.add_list("ALL_LIST", all, vec![types.iter().map(|(_, value)| vec![value.clone()]).collect()]).unwrap();
+    let bytes = builder.build().to_bytes().unwrap();
+
+    let read = Rdbn::read(&bytes).unwrap();
+    let fields = &read.types[0].fields;
+    assert_eq!(fields.iter().map(|field| i16::from(field.field_type)).collect::<Vec<_>>(), [7, 8, 0xB, 0xC, 0xE, 0x10, 0x11]);
+    assert_eq!(fields.iter().map(|field| (field.offset, field.size)).collect::<Vec<_>>(), [(0, 8), (8, 4), (12, 2), (16, 8), (24, 8), (32, 12), (44, 8)]);
+    assert_eq!(read.lists[0].values[0], types.iter().map(|(_, value)| vec![value.clone()]).collect::<Vec<_>>());
+
+    let database = parse_database(&bytes).unwrap();
+    assert_eq!(Database::from_json(&database.serialize()).unwrap().to_bytes().unwrap(), bytes);
+}
+
+#[test]
+fn fields_outside_of_the_rows_are_rejected() {
+    let value_size = item_data().lists[0].value_size;
+
+    for (offset, count) in [(value_size - 1, 1), (-1, 1), (0, -1), (i32::MAX, 1)] {
+        let mut rdbn = item_data();
+        rdbn.types[0].fields[2].offset = offset;
+        rdbn.types[0].fields[2].count = count;
+
+        let error = rdbn.to_bytes().unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData, "{error}");
+    }
+
+    let mut rdbn = item_data();
+    rdbn.lists[0].value_size = -4;
+    assert_eq!(rdbn.to_bytes().unwrap_err().kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn string_table_keeps_its_order_and_hashes() {
+    let rdbn = sample();
+    let bytes = rdbn.to_bytes().unwrap();
+
+    let read = Rdbn::read(&bytes).unwrap();
+    assert_eq!(read.string_lookup, rdbn.string_lookup);
+    assert_eq!(read.string(0xAAAA_0003), Some("ITEM_DATA"));
+    assert_eq!(read.to_bytes().unwrap(), bytes);
+}
+
+#[test]
+fn new_names_are_added_with_their_crc32() {
+    let mut rdbn = sample();
+    rdbn.add_field(0, 3, RdbnFieldDeclaration::new("level", RdbnFieldType::Byte, 1)).unwrap();
+
+    let read = Rdbn::read(&rdbn.to_bytes().unwrap()).unwrap();
+    assert_eq!(read.string_lookup[..6], sample().string_lookup[..]);
+    assert_eq!(read.string_lookup[6..], [RdbnString { hash: 0x9AEA_CC13, name: "level".to_string(), raw: None }]);
+}
+
+#[test]
+fn new_names_are_hashed_like_the_string_table() {
+    let mut rdbn = Rdbn::read(ITEM_PARAM).unwrap();
+    rdbn.add_field(0, 7, RdbnFieldDeclaration::new("level", RdbnFieldType::Byte, 1)).unwrap();
+
+    let read = Rdbn::read(&rdbn.to_bytes().unwrap()).unwrap();
+    assert_eq!(read.string_lookup.last(), Some(&RdbnString { hash: HashType::Crc32Jam.compute(b"level"), name: "level".to_string(), raw: None }));
+}
+
+#[test]
+fn database_keeps_the_string_table() {
+    let bytes = sample().to_bytes().unwrap();
+
+    let database = parse_database(&bytes).unwrap();
+    assert_eq!(database.to_bytes().unwrap(), bytes);
+
+    let database = Database::from_json(&database.serialize()).unwrap();
+    assert_eq!(database.to_bytes().unwrap(), bytes);
+}
+
+#[test]
+fn raw_names_keep_their_bytes_and_hash() {
+    let mut rdbn = sample();
+    rdbn.types[0].name = "ITEM_\u{FFFD}".to_string();
+    rdbn.string_lookup[2] = RdbnString { hash: 0xAAAA_0003, name: "ITEM_\u{FFFD}".to_string(), raw: Some(b"ITEM_\x83".to_vec()) };
+    let bytes = rdbn.to_bytes().unwrap();
+
+    let read = Rdbn::read_with_decoding(&bytes, StringDecoding::Raw).unwrap();
+    assert_eq!(read.string_lookup, rdbn.string_lookup);
+    assert_eq!(read.to_bytes().unwrap(), bytes);
+
+    let read = Rdbn::read_with_decoding(&bytes, StringDecoding::Lossy).unwrap();
+    assert_eq!(read.types[0].name, "ITEM_\u{FFFD}");
+    assert_eq!(read.string_lookup[2].raw, None);
+}
+
+#[test]
+fn dictionary_names_keep_their_hash() {
+    let mut rdbn = sample();
+    rdbn.string_lookup.remove(2);
+    rdbn.dictionary_names.insert(0xBBBB_0003, "ITEM_DATA".to_string());
+    let bytes = rdbn.to_bytes().unwrap();
+
+    assert!(matches!(Rdbn::read(&bytes), Err(CfgBinError::UnknownHash { hash: 0xBBBB_0003, .. })));
+
+    let mut dictionary = HashDictionary::new();
+    dictionary.insert(0xBBBB_0003, "ITEM_DATA".to_string());
+
+    let read = Rdbn::read_with_dictionary(&bytes, StringDecoding::Raw, &dictionary).unwrap();
+    assert_eq!(read.types[0].name, "ITEM_DATA");
+    assert_eq!(read.dictionary_names, rdbn.dictionary_names);
+    assert_eq!(read.to_bytes().unwrap(), bytes);
+
+    let database = parse_database_with_dictionary(&bytes, StringDecoding::Raw, &dictionary).unwrap();
+    assert_eq!(Database::from_json(&database.serialize()).unwrap().to_bytes().unwrap(), bytes);
+}
+
+#[test]
+fn out_of_range_indexes_are_rejected() {
+    let json = parse_database(ITEM_PARAM).unwrap().serialize();
+    assert!(json.contains(r#""type_index": 0"#) && json.contains(r#""field_index": 0"#));
+
+    for json in [json.replace(r#""type_index": 0"#, r#""type_index": 1099511627776"#), json.replace(r#""field_index": 0"#, r#""field_index": 1099511627776"#)] {
+        let error = Database::from_json(&json).unwrap().to_bytes().unwrap_err();
+        assert!(error.to_string().contains("but a file holds at most 32767"), "{error}");
+    }
+}
+
+#[test]
+fn builder_lays_out_the_fields() {
+    let mut builder = RdbnBuilder::new();
+    let chara = builder.add_type("CHARA", 0, vec![
+        RdbnFieldDeclaration::new("level", RdbnFieldType::Byte, 1),
+        RdbnFieldDeclaration::new("id", RdbnFieldType::Int, 1),
+        RdbnFieldDeclaration::new("stats", RdbnFieldType::Short, 3),
+    ]).unwrap();
+    let list = builder.add_list("CHARA_LIST", chara, Vec::new()).unwrap();
+    builder.push_row(list, vec![vec![RdbnValue::Byte(5)], vec![RdbnValue::Int(-1)], vec![RdbnValue::Short(1), RdbnValue::Short(2), RdbnValue::Short(3)]]).unwrap();
+
+    let read = Rdbn::read(&builder.build().to_bytes().unwrap()).unwrap();
+    assert_eq!(read.types[0].fields.iter().map(|field| field.offset).collect::<Vec<_>>(), [0, 4, 8]);
+    assert_eq!(read.lists[0].value_size, 16);
+    assert_eq!(read.lists[0].values[0][2], [RdbnValue::Short(1), RdbnValue::Short(2), RdbnValue::Short(3)]);
+}
+
+#[test]
+fn builder_rejects_bad_declarations() {
+    let mut builder = RdbnBuilder::new();
+    let id = || RdbnFieldDeclaration::new("id", RdbnFieldType::Int, 1);
+
+    assert!(builder.add_type("", 0, vec![id()]).is_err());
+    assert!(builder.add_type("ITEM", 0, vec![id(), id()]).is_err());
+    let item = builder.add_type("ITEM", 0, vec![id()]).unwrap();
+
+    assert!(builder.add_list("ITEM_LIST", item + 1, Vec::new()).is_err());
+    assert!(builder.add_list("ITEM_LIST", item, vec![vec![vec![RdbnValue::Int(1), RdbnValue::Int(2)]]]).is_err());
+    let list = builder.add_list("ITEM_LIST", item, Vec::new()).unwrap();
+    assert!(builder.add_list("ITEM_LIST", item, Vec::new()).is_err());
+
+    assert!(builder.push_row(list, vec![vec![RdbnValue::Float(1.0)]]).is_err());
+    assert!(builder.push_row(list + 1, vec![vec![RdbnValue::Int(1)]]).is_err());
+}
+
+#[test]
+fn builder_extends_a_file_that_was_read() {
+    let original = Rdbn::read(ITEM_PARAM).unwrap();
+    let mut row = original.lists[0].values[0].clone();
+    row[1] = vec![RdbnValue::String("Elixir".to_string())];
+
+    let mut builder = RdbnBuilder::from(original.clone());
+    builder.add_list("ITEM_SHOP_LIST", 0, vec![row]).unwrap();
+
+    let read = Rdbn::read(&builder.build().to_bytes().unwrap()).unwrap();
+    assert_eq!(read.types, original.types);
+    assert_eq!(read.lists[..1], original.lists[..]);
+    assert_eq!(read.lists[1].value_size, 32);
+    assert_eq!(read.lists[1].values[0][1], [RdbnValue::String("Elixir".to_string())]);
+    assert_eq!(read.string_lookup[..9], original.string_lookup[..]);
+}
+
+#[test]
+fn template_keeps_the_repeated_types() {
+    let mut database = parse_database(CHARA_PARAM).unwrap();
+    *database.metadata_mut() = FormatMetadata::None;
+
+    let read = Rdbn::read(&database.to_bytes_with_template(CHARA_PARAM).unwrap()).unwrap();
+    assert_eq!(read.types.len(), 3);
+    assert_eq!(read.lists.iter().map(|list| list.type_index).collect::<Vec<_>>(), [0, 1, 2]);
+}
+
+#[test]
+fn string_offsets_outside_of_the_string_table_are_kept() {
+    let mut rdbn = sample();
+    rdbn.lists[0].values[1][1] = vec![RdbnValue::StringOffset(0xDEAD_BEEF)];
+    let bytes = rdbn.to_bytes().unwrap();
+
+    let read = Rdbn::read(&bytes).unwrap();
+    assert_eq!(read.lists[0].values[1][1], [RdbnValue::StringOffset(0xDEAD_BEEF)]);
+    assert_eq!(read.to_bytes().unwrap(), bytes);
+    let database = parse_database(&bytes).unwrap();
+    assert_eq!(database.table("ITEM_LIST").unwrap().rows()[1].values[1], [Value::StringOffset(0xDEAD_BEEF)]);
+    assert_eq!(database.to_bytes().unwrap(), bytes);
+
+    assert!(matches!(
+        Rdbn::read_with_decoding(&bytes, StringDecoding::Strict),
+        Err(CfgBinError::StringOffsetOutOfRange { string_offset: 0xDEAD_BEEF, .. })
+    ));
+
+    // An offset that the string table covers would be read back as a string
+    rdbn.lists[0].values[1][1] = vec![RdbnValue::StringOffset(4)];
+    assert_eq!(rdbn.to_bytes().unwrap_err().kind(), ErrorKind::InvalidData);
+}
\ No newline at end of file
@@ -0,0 +1,42 @@
+mod common;
+
+use common::item_data;
+use ievr_cfg_bin_editor_core::{parse_database, Database, RowMismatch, RowMismatchKind, Value};
+
+fn sample() -> Database {
+    parse_database(&item_data().to_bytes().unwrap()).unwrap()
+}
+
+#[test]
+fn rows_are_inserted_removed_and_duplicated() {
+    let mut database = sample();
+    let table = database.table_mut("ITEM_LIST").unwrap();
+
+    let row = table.default_row();
+    assert_eq!(row.values, [vec![Value::Int(0)], vec![Value::String(String::new())], vec![Value::Short(0)]]);
+    table.insert_row(0, row).unwrap();
+
+    table.duplicate_row(1).unwrap().values[0] = vec![Value::Int(3)];
+    assert_eq!(table.rows().iter().map(|row| row.values[0][0].clone()).collect::<Vec<_>>(), [Value::Int(0), Value::Int(1), Value::Int(3), Value::Int(2)]);
+
+    assert_eq!(table.remove_row(0).unwrap().values[0], [Value::Int(0)]);
+    assert!(table.remove_row(3).is_none());
+    assert!(table.duplicate_row(3).is_none());
+
+    let bytes = database.to_bytes().unwrap();
+    assert_eq!(parse_database(&bytes).unwrap().table("ITEM_LIST").unwrap().rows().len(), 3);
+}
+
+#[test]
+fn insert_row_rejects_bad_rows() {
+    let mut database = sample();
+    let table = database.table_mut("ITEM_LIST").unwrap();
+
+    let error = table.insert_row(3, table.default_row()).unwrap_err();
+    assert_eq!(error, RowMismatch { table: "ITEM_LIST".to_string(), row: 3, kind: RowMismatchKind::OutOfRange { rows: 2 } });
+
+    let mut row = table.default_row();
+    row.values[2].pop();
+    assert!(matches!(table.push_row(row), Err(RowMismatch { kind: RowMismatchKind::ValueCount { column: 2, expected: 1, found: 0 }, .. })));
+    assert_eq!(table.rows().len(), 2);
+}
\ No newline at end of file
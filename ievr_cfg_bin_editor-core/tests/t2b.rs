@@ -0,0 +1,217 @@
+mod common;
+
+use common::{patched, ITEM_INFO};
+use ievr_cfg_bin_editor_core::{
+    parse_database, CfgBinError, Database, HashDictionary, FormatMetadata, HashType, T2b, T2bBlock, T2bBuilder, T2bEncoding, T2bEntry, T2bEntryValue,
+    T2bNode, T2bTree, T2bValue, T2bValueType, Value, ValueLength,
+};
+
+/// Whether `bytes` appear in the file
+fn contains(file: &[u8], bytes: &[u8]) -> bool {
+    file.windows(bytes.len()).any(|window| window == bytes)
+}
+
+/// The file read with the name of its first item replaced
+fn renamed(file: &[u8], name: &str) -> Database {
+    let mut database = parse_database(file).unwrap();
+    database.table_mut("ITEM_INFO").unwrap().rows_mut()[0].values[1] = vec![Value::String(name.to_string())];
+    database
+}
+
+/// The string count of the entry section header
+fn string_data_count(file: &[u8]) -> u32 {
+    u32::from_le_bytes(file[0xC..0x10].try_into().unwrap())
+}
+
+fn set_encoding(database: &mut Database, encoding: T2bEncoding) {
+    let FormatMetadata::T2b(metadata) = database.metadata_mut() else {
+        panic!("{:?}", database.metadata())
+    };
+    metadata.encoding = encoding;
+}
+
+#[test]
+fn string_count_follows_the_strings() {
+    let mut t2b = T2b::read(ITEM_INFO).unwrap();
+    assert_eq!(t2b.opaque.string_data_count, None);
+
+    t2b.entries.insert(4, T2bEntry {
+        name: "ITEM_INFO".to_string(),
+        values: vec![T2bValue::Integer(3).into(), T2bValue::String("Elixir".to_string()).into(), T2bValue::F32(2.0).into()],
+    });
+    assert_eq!(string_data_count(&t2b.to_bytes().unwrap()), 3);
+}
+
+#[test]
+fn unexpected_string_count_is_kept() {
+    let mut t2b = T2b::read(ITEM_INFO).unwrap();
+    t2b.opaque.string_data_count = Some(7);
+    let bytes = t2b.to_bytes().unwrap();
+
+    let read = T2b::read(&bytes).unwrap();
+    assert_eq!(read.opaque.string_data_count, Some(7));
+    assert_eq!(read.to_bytes().unwrap(), bytes);
+}
+
+/// The value of the leading entry, which holds the size of the file
+fn size_entry(file: &[u8]) -> T2bValue {
+    T2b::read(file).unwrap().entries[0].values[0].value.clone()
+}
+
+#[test]
+fn size_entry_follows_the_edits() {
+    let mut t2b = T2b::read(ITEM_INFO).unwrap();
+    t2b.entries.push(T2bEntry { name: "ITEM_NOTE".to_string(), values: vec![T2bValue::String("Restores HP".to_string()).into()] });
+
+    let bytes = t2b.to_bytes().unwrap();
+    assert_eq!(size_entry(&bytes), T2bValue::Integer(bytes.len() as i32));
+}
+
+#[test]
+fn leading_values_that_are_not_the_size_are_kept() {
+    let mut builder = T2bBuilder::new("ITEM_INFO_SIZE", T2bEncoding::Utf8, ValueLength::Int, HashType::Crc32Jam);
+    builder.add_entry("ITEM_NOTE", vec![T2bValue::String("Restores HP".to_string())]).unwrap();
+    let mut t2b = builder.build().unwrap();
+    t2b.entries[0].name = "X".to_string();
+    t2b.entries[0].values[0] = T2bValue::Integer(5).into();
+    t2b.opaque.size_entry = false;
+    let bytes = t2b.to_bytes().unwrap();
+
+    let mut read = T2b::read(&bytes).unwrap();
+    assert!(!read.opaque.size_entry);
+    read.entries.push(T2bEntry { name: "ITEM_NOTE".to_string(), values: vec![T2bValue::String("Restores MP".to_string()).into()] });
+    assert_eq!(size_entry(&read.to_bytes().unwrap()), T2bValue::Integer(5));
+
+    let database = parse_database(&bytes).unwrap();
+    assert_eq!(size_entry(&database.to_bytes().unwrap()), T2bValue::Integer(5));
+    assert_eq!(Database::from_json(&database.serialize()).unwrap().to_bytes().unwrap(), bytes);
+    assert!(T2b::read(ITEM_INFO).unwrap().opaque.size_entry);
+}
+
+#[test]
+fn database_size_entry_follows_the_edits() {
+    let mut database = parse_database(ITEM_INFO).unwrap();
+    let table = database.table_mut("ITEM_INFO").unwrap();
+    let mut row = table.rows()[1].clone();
+    row.position = None;
+    table.rows_mut().push(row);
+
+    let bytes = database.to_bytes().unwrap();
+    assert_eq!(size_entry(&bytes), T2bValue::Integer(bytes.len() as i32));
+    assert_eq!(T2b::read(&bytes).unwrap().entries.len(), 7);
+}
+
+#[test]
+fn dictionary_names_keep_their_checksum() {
+    let mut t2b = T2b::read(ITEM_INFO).unwrap();
+    t2b.dictionary_names.insert(0xCCCC_0001, "ITEM_FLAGS".to_string());
+    let bytes = t2b.to_bytes().unwrap();
+
+    assert!(matches!(T2b::read(&bytes), Err(CfgBinError::UnknownHash { hash: 0xCCCC_0001, .. })));
+
+    let mut dictionary = HashDictionary::new();
+    dictionary.insert(0xCCCC_0001, "ITEM_FLAGS".to_string());
+
+    let read = T2b::read_with_dictionary(&bytes, &dictionary).unwrap();
+    assert_eq!(read.entries[1..], t2b.entries[1..]);
+    assert_eq!(read.entries[0].values[0].value, T2bValue::Integer(bytes.len() as i32));
+    assert_eq!(read.to_bytes().unwrap(), bytes);
+}
+
+#[test]
+fn strings_follow_the_encoding_of_the_footer() {
+    let shift_jis = patched(ITEM_INFO, ITEM_INFO.len() - 0xA, &[0, 0]);
+    let mut database = renamed(&shift_jis, "円堂守");
+    assert_eq!(database.encoding(), Some(T2bEncoding::ShiftJis));
+
+    let bytes = database.to_bytes().unwrap();
+    assert!(contains(&bytes, &[0x89, 0x7E, 0x93, 0xB0, 0x8E, 0xE7, 0]));
+    let read = parse_database(&bytes).unwrap();
+    assert!(matches!(&read.table("ITEM_INFO").unwrap().rows()[0].values[1][..], [Value::String(name)] if name == "円堂守"));
+
+    set_encoding(&mut database, T2bEncoding::Utf8);
+    assert!(contains(&database.to_bytes().unwrap(), "円堂守\0".as_bytes()));
+
+    set_encoding(&mut database, T2bEncoding::Unknown(5));
+    assert_eq!(parse_database(&database.to_bytes().unwrap()).unwrap().encoding(), Some(T2bEncoding::Unknown(5)));
+}
+
+#[test]
+fn strings_that_cannot_be_encoded_are_rejected() {
+    let mut database = renamed(ITEM_INFO, "\u{1F600}");
+    assert!(database.to_bytes().is_ok());
+
+    set_encoding(&mut database, T2bEncoding::ShiftJis);
+    assert!(database.to_bytes().is_err());
+}
+
+#[test]
+fn tree_groups_the_blocks() {
+    let t2b = T2b::read(ITEM_INFO).unwrap();
+    let mut tree = t2b.tree();
+
+    assert_eq!(tree.nodes.len(), 3);
+    let T2bNode::Block(block) = &mut tree.nodes[1] else {
+        panic!("{:?}", tree.nodes[1])
+    };
+    assert_eq!(block.name(), "ITEM_INFO");
+    assert_eq!(block.children.len(), 2);
+    assert!(block.counts_children);
+
+    block.children.pop();
+    let mut edited = T2b::read(ITEM_INFO).unwrap();
+    edited.set_tree(tree);
+    assert_eq!(edited.entries.len(), t2b.entries.len() - 1);
+    assert_eq!(edited.entries[1].values[0].value, T2bValue::Integer(1));
+}
+
+#[test]
+fn unmatched_markers_are_plain_entries() {
+    let names = ["A_BEGIN", "B_BEG", "VALUE", "A_END", "C_END"];
+    let entry = |name: &str| T2bEntry { name: name.to_string(), values: vec![T2bEntryValue { r#type: T2bValueType::Integer, value: T2bValue::Integer(1) }] };
+
+    let tree = T2bTree::from_entries(names.iter().map(|name| entry(name)).collect());
+    assert!(matches!(&tree.nodes[..], [T2bNode::Block(block), T2bNode::Entry(_)] if block.children.len() == 2 && !block.counts_children));
+    assert_eq!(tree.into_entries().iter().map(|entry| entry.name.as_str()).collect::<Vec<_>>(), names);
+}
+
+#[test]
+fn new_blocks_count_their_children() {
+    let child = T2bNode::Entry(T2bEntry { name: "VALUE".to_string(), values: Vec::new() });
+    let tree = T2bTree { nodes: vec![T2bNode::Block(T2bBlock::new("LIST", vec![child.clone(), child]))] };
+
+    let entries = tree.into_entries();
+    assert_eq!(entries.iter().map(|entry| entry.name.as_str()).collect::<Vec<_>>(), ["LIST_BEGIN", "VALUE", "VALUE", "LIST_END"]);
+    assert_eq!(entries[0].values[0].value, T2bValue::Integer(2));
+}
+
+#[test]
+fn builder_writes_blocks_and_the_size() {
+    let mut builder = T2bBuilder::new("CHARA_INFO_SIZE", T2bEncoding::Utf8, ValueLength::Long, HashType::Crc32Jam);
+    let child = |id: i64| T2bNode::Entry(T2bEntry { name: "CHARA".to_string(), values: vec![T2bValue::Long(id).into(), T2bValue::F64(0.5).into()] });
+    builder.add_block(T2bBlock::new("CHARA_INFO", vec![child(1), child(1 << 40)])).unwrap();
+    builder.add_entry("CHARA_COUNT", vec![T2bValue::Long(2)]).unwrap();
+    let t2b = builder.build().unwrap();
+
+    let bytes = t2b.to_bytes().unwrap();
+    let read = T2b::read(&bytes).unwrap();
+    assert_eq!((read.value_length, read.hash_type), (ValueLength::Long, HashType::Crc32Jam));
+    assert_eq!(read.to_bytes().unwrap(), bytes);
+    assert_eq!(read.entries[0].values[0].value, T2bValue::Long(bytes.len() as i64));
+    assert_eq!(read.entries.iter().map(|entry| entry.name.as_str()).collect::<Vec<_>>(), ["CHARA_INFO_SIZE", "CHARA_INFO_BEGIN", "CHARA", "CHARA", "CHARA_INFO_END", "CHARA_COUNT"]);
+    assert_eq!(read.entries[1].values[0].value, T2bValue::Long(2));
+}
+
+#[test]
+fn builder_rejects_entries_the_file_cannot_store() {
+    let mut builder = T2bBuilder::new("CHARA_INFO_SIZE", T2bEncoding::Utf8, ValueLength::Int, HashType::Crc32Standard);
+
+    assert!(builder.add_entry("", Vec::new()).is_err());
+    assert!(builder.add_entry("CHARA", vec![T2bValue::Long(1 << 40)]).is_err());
+    assert!(builder.add_entry("CHARA", vec![T2bValue::Integer(1); 256]).is_err());
+    assert!(builder.add_block(T2bBlock::new("CHARA_INFO", vec![T2bNode::Entry(T2bEntry { name: String::new(), values: Vec::new() })])).is_err());
+    assert!(T2bBuilder::new("", T2bEncoding::Utf8, ValueLength::Int, HashType::Crc32Standard).build().is_err());
+
+    builder.add_entry("CHARA", vec![T2bValue::Integer(1); 255]).unwrap();
+    assert_eq!(builder.build().unwrap().entries.len(), 2);
+}
\ No newline at end of file
@@ -1,25 +1,38 @@
-use std::{env, error::Error, fs::File, io::Write, path::PathBuf};
+use std::{env, error::Error, fs::{self, File}, io::Write, path::PathBuf};
 
 use memmap2::Mmap;
 
-use ievr_cfg_bin_editor_core::parse_database;
+use ievr_cfg_bin_editor_core::{parse_database, Database};
+
+const USAGE: &str = "Usage: ievr_cfg_bin_editor <input_file>\n       ievr_cfg_bin_editor --import <json_file> <output_file> [template_file]";
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let input_path = env::args()
-        .nth(1)
-        .expect("Usage: ievr_cfg_bin_editor <input_file>");
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match args.as_slice() {
+        [input_path] => export_json(input_path),
+        [flag, json_path, output_path] if flag == "--import" => import_json(json_path, output_path, None),
+        [flag, json_path, output_path, template_path] if flag == "--import" => import_json(json_path, output_path, Some(template_path)),
+        _ => {
+            eprintln!("{USAGE}");
+            std::process::exit(1);
+        }
+    }
+}
 
-    let input_path = input_path.trim_matches('"').trim_end_matches("\\"); // This removes trailling backslashes and all quotes
-    
-    let file_path = PathBuf::from(&input_path);
-    let file = File::open(file_path).unwrap();
+fn clean_path(path: &str) -> PathBuf {
+    PathBuf::from(path.trim_matches('"').trim_end_matches("\\")) // This removes trailling backslashes and all quotes
+}
+
+fn export_json(input_path: &str) -> Result<(), Box<dyn Error>> {
+    let file_path = clean_path(input_path);
+    let file = File::open(&file_path).unwrap();
 
     let mmap = unsafe { Mmap::map(&file).unwrap() };
 
-    let database = parse_database(&mmap).expect("Failed to parse database");
+    let database = parse_database(&mmap)?;
 
-    let path_buf = PathBuf::from(&input_path);
-    let input_file_name = path_buf
+    let input_file_name = file_path
         .file_name()
         .expect("Invalid input file")
         .to_string_lossy();
@@ -31,3 +44,20 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+fn import_json(json_path: &str, output_path: &str, template_path: Option<&String>) -> Result<(), Box<dyn Error>> {
+    let json = fs::read_to_string(clean_path(json_path))?;
+    let database = Database::from_json(&json)?;
+
+    let bytes = match template_path {
+        Some(template_path) => {
+            let template = fs::read(clean_path(template_path))?;
+            database.to_bytes_with_template(&template)?
+        },
+        None => database.to_bytes()?,
+    };
+
+    fs::write(clean_path(output_path), bytes)?;
+
+    Ok(())
+}